@@ -0,0 +1,124 @@
+use std::{fmt::Write, time::Duration};
+
+use crate::{error::Result, input::InputSource, params::Setting, solution::Part, DAYS};
+
+/// Summary of the timings of one stage over several repetitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// None if there are no timings.
+    pub fn from_timings(timings: &[Duration]) -> Option<Self> {
+        let mut sorted = timings.to_vec();
+        sorted.sort();
+        Some(Self {
+            min: *sorted.first()?,
+            median: sorted[sorted.len() / 2],
+            max: *sorted.last()?,
+        })
+    }
+}
+
+/// Format with a unit suited to the size, e.g. "12.345 ms".
+pub fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos() as f64;
+    if nanos < 1e3 {
+        format!("{:.0} ns", nanos)
+    } else if nanos < 1e6 {
+        format!("{:.3} µs", nanos / 1e3)
+    } else if nanos < 1e9 {
+        format!("{:.3} ms", nanos / 1e6)
+    } else {
+        format!("{:.3} s", nanos / 1e9)
+    }
+}
+
+/// Timings for the stages of one day over all repetitions.
+#[derive(Debug, Default)]
+pub struct DayTimings {
+    pub parse: Vec<Duration>,
+    pub part1: Vec<Duration>,
+    pub part2: Vec<Duration>,
+}
+
+/// Solve a day `reps` times, timing each stage.
+pub fn bench_day(
+    day: usize,
+    source: &InputSource,
+    part: Part,
+    settings: &[Setting],
+    reps: usize,
+) -> Result<DayTimings> {
+    let mut timings = DayTimings::default();
+    for _ in 0..reps {
+        let solved = DAYS[day - 1](source, part, settings).map_err(|e| e.in_day(day))?;
+        timings.parse.push(solved.parse_time);
+        for answer in solved.answers {
+            match answer.part {
+                1 => timings.part1.push(answer.elapsed),
+                _ => timings.part2.push(answer.elapsed),
+            }
+        }
+    }
+    Ok(timings)
+}
+
+/// Table of min/median/max for each day and stage.
+pub fn table(results: &[(usize, DayTimings)], reps: usize) -> String {
+    let mut out = String::new();
+    writeln!(out, "{} repetitions", reps).unwrap();
+    writeln!(
+        out,
+        "{:<4} {:<7} {:>12} {:>12} {:>12}",
+        "Day", "Stage", "Min", "Median", "Max"
+    )
+    .unwrap();
+    for (day, timings) in results {
+        let stages = [
+            ("parse", &timings.parse),
+            ("part 1", &timings.part1),
+            ("part 2", &timings.part2),
+        ];
+        for (stage, times) in stages {
+            if let Some(stats) = Stats::from_timings(times) {
+                writeln!(
+                    out,
+                    "{:<4} {:<7} {:>12} {:>12} {:>12}",
+                    format!("{:02}", day),
+                    stage,
+                    format_duration(stats.min),
+                    format_duration(stats.median),
+                    format_duration(stats.max)
+                )
+                .unwrap();
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::from_timings(&[ms(5), ms(1), ms(9), ms(3), ms(4)]).unwrap();
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.median, ms(4));
+        assert_eq!(stats.max, ms(9));
+        assert_eq!(Stats::from_timings(&[]), None);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(Duration::from_nanos(999)), "999 ns");
+        assert_eq!(format_duration(Duration::from_micros(1500)), "1.500 ms");
+        assert_eq!(format_duration(Duration::from_secs(2)), "2.000 s");
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fmt, ops::Range};
+
+use crate::{
+    error::Result,
+    generate::generate,
+    parallel::{catch_panic, quietly},
+    params::Params,
+    solution::{Part, Solution},
+    CROSSCHECKS,
+};
+
+/// The fast and reference answers for one part of a generated input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub part: u8,
+    pub fast: String,
+    pub reference: String,
+}
+
+impl Comparison {
+    pub fn agrees(&self) -> bool {
+        self.fast == self.reference
+    }
+}
+
+/// A day's crosscheck of the input generated from a seed and size.
+pub type Crosscheck = fn(u64, usize, Part) -> Result<Vec<Comparison>>;
+
+/// Solve a generated input both ways, for each part with a reference
+/// solver. Parts without one are left out, so a day with none gives no
+/// comparisons.
+pub fn crosscheck<S: Solution>(seed: u64, size: usize, part: Part) -> Result<Vec<Comparison>> {
+    let reference1 = S::REFERENCE1.filter(|_| part.includes(1));
+    let reference2 = S::REFERENCE2.filter(|_| part.includes(2));
+    if reference1.is_none() && reference2.is_none() {
+        return Ok(vec![]);
+    }
+    let generated = generate::<S>(seed, size);
+    let mut params = Params::new(S::PARAMS, false);
+    for (name, value) in &generated.params {
+        params.set(name, value)?;
+    }
+    let input = S::parse(&generated.input, &params)?;
+
+    let mut comparisons = vec![];
+    if let Some(reference) = reference1 {
+        comparisons.push(Comparison {
+            part: 1,
+            fast: S::part1(&input)?.to_string(),
+            reference: reference(&input).to_string(),
+        });
+    }
+    if let Some(reference) = reference2 {
+        comparisons.push(Comparison {
+            part: 2,
+            fast: S::part2(&input)?.to_string(),
+            reference: reference(&input).to_string(),
+        });
+    }
+    Ok(comparisons)
+}
+
+/// The comparisons for one day over a range of seeds, and the seeds
+/// which failed to solve.
+#[derive(Debug, Clone, Default)]
+pub struct DayCrosscheck {
+    pub day: usize,
+    pub comparisons: Vec<(u64, Comparison)>,
+    pub failures: Vec<(u64, String)>,
+}
+
+impl DayCrosscheck {
+    pub fn agreed(&self) -> usize {
+        self.comparisons.iter().filter(|(_, c)| c.agrees()).count()
+    }
+}
+
+// Failures and disagreements, then a line of totals for days with a
+// reference solver.
+impl fmt::Display for DayCrosscheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (seed, reason) in &self.failures {
+            writeln!(f, "Day {:02} seed {}: {}", self.day, seed, reason)?;
+        }
+        for (seed, comparison) in self.comparisons.iter().filter(|(_, c)| !c.agrees()) {
+            writeln!(
+                f,
+                "Day {:02} part {} seed {}: {}, but the reference gives {}",
+                self.day, comparison.part, seed, comparison.fast, comparison.reference
+            )?;
+        }
+        if !self.comparisons.is_empty() {
+            writeln!(
+                f,
+                "Day {:02}: {} of {} agreed",
+                self.day,
+                self.agreed(),
+                self.comparisons.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Crosscheck a day on the input generated from each seed. Panics are
+/// recorded as failures.
+pub fn crosscheck_day(day: usize, seeds: Range<u64>, size: usize, part: Part) -> DayCrosscheck {
+    let mut result = DayCrosscheck {
+        day,
+        ..Default::default()
+    };
+    for seed in seeds {
+        match catch_panic(|| CROSSCHECKS[day - 1](seed, size, part)) {
+            // Every seed gives none for a day without a reference solver
+            Ok(comparisons) if comparisons.is_empty() => break,
+            Ok(comparisons) => result
+                .comparisons
+                .extend(comparisons.into_iter().map(|c| (seed, c))),
+            Err(reason) => result.failures.push((seed, reason)),
+        }
+    }
+    result
+}
+
+/// Totals over the days crosschecked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub agreed: usize,
+    pub disagreed: usize,
+    pub failed: usize,
+}
+
+impl Summary {
+    pub fn all_agreed(&self) -> bool {
+        self.disagreed == 0 && self.failed == 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.agreed + self.disagreed + self.failed == 0
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} agreed, {} disagreed, {} failed",
+            self.agreed, self.disagreed, self.failed
+        )
+    }
+}
+
+/// Crosscheck each of the days, passing the results for each to `on_day`
+/// as it finishes. Panics are recorded rather than shown on stderr.
+pub fn crosscheck_days(
+    days: impl IntoIterator<Item = usize>,
+    seeds: Range<u64>,
+    size: usize,
+    part: Part,
+    mut on_day: impl FnMut(&DayCrosscheck),
+) -> Summary {
+    let mut summary = Summary::default();
+    quietly(|| {
+        for day in days {
+            let result = crosscheck_day(day, seeds.clone(), size, part);
+            let agreed = result.agreed();
+            summary.agreed += agreed;
+            summary.disagreed += result.comparisons.len() - agreed;
+            summary.failed += result.failures.len();
+            on_day(&result);
+        }
+    });
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day01::Day01, day20::Day20};
+
+    #[test]
+    fn compares_parts_with_references() {
+        let comparisons = crosscheck::<Day20>(0, 10, Part::Both).unwrap();
+        assert_eq!(
+            comparisons.iter().map(|c| c.part).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert!(comparisons.iter().all(Comparison::agrees));
+        assert_eq!(crosscheck::<Day20>(0, 10, Part::Two).unwrap().len(), 1);
+        // Day 1 has no reference solvers
+        assert!(crosscheck::<Day01>(0, 10, Part::Both).unwrap().is_empty());
+    }
+
+    #[test]
+    fn crosscheck_several_days() {
+        let mut days = vec![];
+        let summary = crosscheck_days([1, 20], 0..3, 10, Part::Both, |day| {
+            days.push((day.day, day.comparisons.len()))
+        });
+        assert_eq!(days, [(1, 0), (20, 6)]);
+        assert_eq!(summary.to_string(), "6 agreed, 0 disagreed, 0 failed");
+        assert!(summary.all_agreed());
+    }
+}
@@ -0,0 +1,112 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Spots when a simulation returns to an earlier state, from a key
+/// summarising the state after each step.
+///
+/// The key needs to capture everything that affects later steps, or a
+/// repeat may be reported too early.
+#[derive(Debug)]
+pub struct CycleDetector<K> {
+    seen: HashMap<K, usize>,
+    metrics: Vec<i64>,
+}
+
+/// A repeating run of states, with the metric recorded up to the end of
+/// its first repetition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// The first step in the cycle.
+    pub start: usize,
+    /// Number of steps before the state repeats.
+    pub length: usize,
+    metrics: Vec<i64>,
+}
+
+impl<K: Eq + Hash> CycleDetector<K> {
+    pub fn new() -> Self {
+        Self {
+            seen: HashMap::new(),
+            metrics: vec![],
+        }
+    }
+
+    /// Record the state after the next step (the first call being the
+    /// initial state), with a metric to extrapolate, such as a height or
+    /// a count. Returns the cycle once a state repeats.
+    pub fn record(&mut self, key: K, metric: i64) -> Option<Cycle> {
+        let step = self.metrics.len();
+        self.metrics.push(metric);
+        match self.seen.get(&key) {
+            Some(&start) => Some(Cycle {
+                start,
+                length: step - start,
+                metrics: self.metrics.clone(),
+            }),
+            None => {
+                self.seen.insert(key, step);
+                None
+            }
+        }
+    }
+}
+
+impl<K: Eq + Hash> Default for CycleDetector<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cycle {
+    /// The change in the metric over each repetition.
+    pub fn metric_increase(&self) -> i64 {
+        self.metrics[self.start + self.length] - self.metrics[self.start]
+    }
+
+    /// The metric after `step` steps, whether recorded or not.
+    pub fn extrapolate(&self, step: u64) -> i64 {
+        if step < self.metrics.len() as u64 {
+            return self.metrics[step as usize];
+        }
+        let into_cycle = step - self.start as u64;
+        let repeats = (into_cycle / self.length as u64) as i64;
+        let offset = (into_cycle % self.length as u64) as usize;
+        self.metrics[self.start + offset] + repeats * self.metric_increase()
+    }
+
+    /// The state index which `step` is equivalent to, within the first
+    /// run through the cycle.
+    pub fn equivalent_step(&self, step: u64) -> usize {
+        if step < self.start as u64 {
+            step as usize
+        } else {
+            self.start + ((step - self.start as u64) % self.length as u64) as usize
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_and_extrapolate() {
+        // States 0, 1, 2, 3, 4, 2, 3, 4, ... while the metric climbs by one
+        // each step, so by 3 each cycle.
+        let mut detector = CycleDetector::new();
+        let mut state = 0;
+        let mut step = 0;
+        let cycle = loop {
+            if let Some(cycle) = detector.record(state, step) {
+                break cycle;
+            }
+            state = if state == 4 { 2 } else { state + 1 };
+            step += 1;
+        };
+        assert_eq!((cycle.start, cycle.length), (2, 3));
+        assert_eq!(cycle.metric_increase(), 3);
+        assert_eq!(cycle.extrapolate(4), 4);
+        assert_eq!(cycle.extrapolate(1000), 1000);
+        assert_eq!(cycle.equivalent_step(1), 1);
+        assert_eq!(cycle.equivalent_step(9), 3);
+    }
+}
@@ -1,56 +1,170 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Error},
+use std::fmt::Write;
+
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    input::numbered_paragraphs,
+    params::Params,
+    solution::Solution,
 };
 
-pub fn read_int_list(filename: &str) -> Result<Vec<Option<i32>>, Error> {
-    let f = File::open(filename)?;
-    Ok(BufReader::new(f)
-        .lines()
-        .map(|l| l.expect("Err"))
-        .map(|l| l.parse::<i32>().ok())  // None for blank lines
-        .collect())
+/// The calories of each item carried by each elf. Elves are separated by
+/// blank lines, and every other line must be a number.
+pub fn read_elves(input: &str) -> Result<Vec<Vec<i32>>> {
+    let mut elves = vec![];
+    for paragraph in numbered_paragraphs(input)? {
+        let mut items = vec![];
+        for line in &paragraph {
+            items.push(line.parse(line.text)?);
+        }
+        elves.push(items);
+    }
+    Ok(elves)
 }
 
-pub fn step1() {
-    // Sum of largest chunk
-    let mut current = 0;
-    let mut max_sum = 0;
-    for value in read_int_list("inputs/day01.txt").unwrap() {
-        if let Some(entry) = value {
-            current += entry;
-        } else {
-            if current > max_sum {
-                max_sum = current;
-            }
-            current = 0;
-        }
+/// An elf's total calories, and its position in the input from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElfTotal {
+    pub index: usize,
+    pub total: i32,
+}
+
+/// The `n` elves carrying the most calories, most first. Elves with the
+/// same total stay in input order.
+pub fn top_n(elves: &[Vec<i32>], n: usize) -> Vec<ElfTotal> {
+    let mut ranked: Vec<ElfTotal> = elves
+        .iter()
+        .enumerate()
+        .map(|(index, items)| ElfTotal {
+            index,
+            total: items.iter().sum(),
+        })
+        .collect();
+    ranked.sort_by_key(|elf| std::cmp::Reverse(elf.total));
+    ranked.truncate(n);
+    ranked
+}
+
+// Every elf's item count, total, rank and share of all the calories.
+fn report(elves: &[Vec<i32>]) -> String {
+    let ranked = top_n(elves, elves.len());
+    let mut ranks = vec![0; elves.len()];
+    for (rank, elf) in ranked.iter().enumerate() {
+        ranks[elf.index] = rank + 1;
     }
-    println!("Maximum sum: {}", max_sum);
+    let overall: i32 = ranked.iter().map(|elf| elf.total).sum();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:>5} {:>5} {:>9} {:>5} {:>7}",
+        "Elf", "Items", "Calories", "Rank", "Share"
+    )
+    .unwrap();
+    for (index, items) in elves.iter().enumerate() {
+        let total: i32 = items.iter().sum();
+        let share = match overall {
+            0 => 0.0,
+            _ => 100.0 * total as f64 / overall as f64,
+        };
+        writeln!(
+            out,
+            "{:>5} {:>5} {:>9} {:>5} {:>6.2}%",
+            index + 1,
+            items.len(),
+            total,
+            ranks[index],
+            share
+        )
+        .unwrap();
+    }
+    writeln!(out, "{} elves carrying {} calories", elves.len(), overall).unwrap();
+    out
 }
 
-pub fn step2() {
-    // Sum of top 3 chunks
-    let mut current = 0;
-    let mut max_sum_1 = 0;
-    let mut max_sum_2 = 0;
-    let mut max_sum_3 = 0;
-    for value in read_int_list("inputs/day01.txt").unwrap() {
-        if let Some(entry) = value {
-            current += entry;
-        } else {
-            if current > max_sum_1 {
-                max_sum_3 = max_sum_2;
-                max_sum_2 = max_sum_1;
-                max_sum_1 = current;
-            } else if current > max_sum_2 {
-                max_sum_3 = max_sum_2;
-                max_sum_2 = current;
-            } else if current > max_sum_3 {
-                max_sum_3 = current;
-            }
-            current = 0;
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<Vec<i32>>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day01.txt");
+    const REPORT: Option<fn(&Self::Input) -> String> = Some(|input| report(input));
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        read_elves(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Most calories carried by one elf
+        Ok(top_n(input, 1).iter().map(|elf| elf.total).sum())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        // Calories carried by the top three
+        Ok(top_n(input, 3).iter().map(|elf| elf.total).sum())
+    }
+
+    // `size` elves, each carrying a few snacks
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut elves = vec![];
+        for _ in 0..size.max(1) {
+            let snacks: Vec<String> = (0..rng.range(1..=6))
+                .map(|_| rng.range(1000..=60000).to_string())
+                .collect();
+            elves.push(snacks.join("\n"));
         }
+        Generated::new(elves.join("\n\n") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day01::part1(&example_input::<Day01>()).unwrap(), 24000);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day01::part2(&example_input::<Day01>()).unwrap(), 45000);
+    }
+
+    #[test]
+    fn read_elves_includes_final_group() {
+        assert_eq!(read_elves("1\n2\n\n\n3").unwrap(), [vec![1, 2], vec![3]]);
+        let e = read_elves("1\n\n2\nx3\n").unwrap_err();
+        assert_eq!((e.line, e.column), (Some(4), Some(1)));
+    }
+
+    #[test]
+    fn top_n_ranks_elves() {
+        let elves = example_input::<Day01>();
+        let top: Vec<(usize, i32)> = top_n(&elves, 3)
+            .iter()
+            .map(|elf| (elf.index, elf.total))
+            .collect();
+        assert_eq!(top, [(3, 24000), (2, 11000), (4, 10000)]);
+        assert_eq!(top_n(&elves, 10).len(), 5);
+    }
+
+    #[test]
+    fn report_example() {
+        let report = report(&example_input::<Day01>());
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[4], "    4     3     24000     1  43.64%");
+        assert_eq!(lines[6], "5 elves carrying 55000 calories");
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day01>(1);
+        assert_eq!(Day01::part1(&input).unwrap(), 69206);
+        assert_eq!(Day01::part2(&input).unwrap(), 197400);
     }
-    println!("Maximum sum of top 3: {}", max_sum_1 + max_sum_2 + max_sum_3);
 }
@@ -1,92 +1,648 @@
 use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+    fmt::{self, Write},
+    path::PathBuf,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
-fn calc_score(line: &str) -> i32 {
-    let opponent_move = match &line[0..1] {
-        "A" => 1,
-        "B" => 2,
-        "C" => 3,
-        _ => panic!("Invalid opponent move"),
-    };
-    let our_move = match &line[2..3] {
-        "X" => 1,
-        "Y" => 2,
-        "Z" => 3,
-        _ => panic!("Invalid our move"),
-    };
-
-    let round_result = match our_move - opponent_move {
-        0 => 3,  // draw
-        1 => 6,  // one ahead in RPS => win
-        -1 => 0, // one behind => loss
-        2 => 0,  // two ahead => one behind => loss
-        -2 => 6, // two behind => one ahead => win
-        _ => panic!("Invalid result"),
-    };
-
-    our_move + round_result
-}
-
-pub fn step1() {
-    // Total score of all RPS rounds
-    let mut score = 0;
-    for line in read_list("inputs/day02.txt") {
-        score += calc_score(&line)
-    }
-    println!("Total score: {}", score);
-}
-
-fn calc_move_and_score(line: &str) -> i32 {
-    let opponent_move = match &line[0..1] {
-        "A" => 1,
-        "B" => 2,
-        "C" => 3,
-        _ => panic!("Invalid opponent move"),
-    };
-    let desired_outcome = match &line[2..3] {
-        "X" => 0, // lose
-        "Y" => 3, // draw
-        "Z" => 6, // win
-        _ => panic!("Invalid outcome"),
-    };
-
-    let our_move = match desired_outcome {
-        // lose: one behind
-        0 => {
-            if opponent_move == 1 {
-                3
-            } else {
-                opponent_move - 1
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::{numbered_lines, InputSource, Line},
+    params::{Param, Params},
+    solution::Solution,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Padding is applied to the name, for tables
+        f.pad(match self {
+            Outcome::Loss => "loss",
+            Outcome::Draw => "draw",
+            Outcome::Win => "win",
+        })
+    }
+}
+
+/// One of the shapes a game can be played with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub name: String,
+    /// Letter for the move in the first column of the strategy guide.
+    pub theirs: char,
+    /// Letter for the move in the second column, when it's read as a move.
+    pub ours: char,
+    /// Score for playing the move, whatever the outcome.
+    pub score: i32,
+}
+
+impl Move {
+    fn new(name: &str, theirs: char, ours: char, score: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            theirs,
+            ours,
+            score,
+        }
+    }
+}
+
+/// The rules of a game like Rock-Paper-Scissors: its moves, which beats
+/// which, and the scores for each move and outcome.
+#[derive(Debug, Clone)]
+pub struct Game {
+    moves: Vec<Move>,
+    // beats[a][b] when move a beats move b
+    beats: Vec<Vec<bool>>,
+    // Scores for a loss, a draw and a win
+    outcome_scores: [i32; 3],
+    // Letters for a loss, a draw and a win, when the second column of the
+    // strategy guide is read as an outcome
+    outcome_letters: [char; 3],
+}
+
+impl Game {
+    /// A game with the moves in cycle order, where each beats those an odd
+    /// number of places before it. With an odd number of moves, every move
+    /// then beats exactly half of the others.
+    pub fn cyclic(moves: Vec<Move>, outcome_scores: [i32; 3]) -> Self {
+        let count = moves.len();
+        let beats = (0..count)
+            .map(|a| {
+                (0..count)
+                    .map(|b| (a + count - b) % count % 2 == 1)
+                    .collect()
+            })
+            .collect();
+        Self {
+            moves,
+            beats,
+            outcome_scores,
+            outcome_letters: ['X', 'Y', 'Z'],
+        }
+    }
+
+    pub fn rock_paper_scissors() -> Self {
+        Self::cyclic(
+            vec![
+                Move::new("Rock", 'A', 'X', 1),
+                Move::new("Paper", 'B', 'Y', 2),
+                Move::new("Scissors", 'C', 'Z', 3),
+            ],
+            [0, 3, 6],
+        )
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Self::cyclic(
+            vec![
+                Move::new("Rock", 'A', 'V', 1),
+                Move::new("Paper", 'B', 'W', 2),
+                Move::new("Scissors", 'C', 'X', 3),
+                Move::new("Spock", 'D', 'Y', 4),
+                Move::new("Lizard", 'E', 'Z', 5),
+            ],
+            [0, 3, 6],
+        )
+    }
+
+    /// Custom rules, with lines of:
+    ///
+    /// - `move <name> <their letter> <our letter> <score>`, for each move
+    ///   in cycle order
+    /// - `beats <name> <name>...`, optionally, listing the moves one beats
+    ///   instead of going by the cycle
+    /// - `outcomes <loss> <draw> <win>`, for scores other than 0, 3 and 6
+    /// - `letters <loss> <draw> <win>`, for outcome letters other than X, Y
+    ///   and Z
+    ///
+    /// and `#` comment lines.
+    pub fn from_rules(input: &str) -> Result<Self> {
+        let mut moves: Vec<Move> = vec![];
+        let mut beats_lines = vec![];
+        let mut outcome_scores = [0, 3, 6];
+        let mut outcome_letters = ['X', 'Y', 'Z'];
+        for line in numbered_lines(input)? {
+            let fields: Vec<&str> = line.text.split_whitespace().collect();
+            let letter = |field: &str| {
+                let mut chars = field.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_uppercase() => Ok(c),
+                    _ => Err(line.field_error(field, "expected a capital letter")),
+                }
+            };
+            match fields.first() {
+                None => continue,
+                Some(first) if first.starts_with('#') => continue,
+                Some(&"move") => {
+                    if fields.len() != 5 {
+                        return Err(line
+                            .error("expected 'move <name> <their letter> <our letter> <score>'"));
+                    }
+                    let mv = Move::new(
+                        fields[1],
+                        letter(fields[2])?,
+                        letter(fields[3])?,
+                        line.parse(fields[4])?,
+                    );
+                    if moves.iter().any(|m| m.name == mv.name) {
+                        return Err(line.field_error(fields[1], "duplicate move"));
+                    }
+                    if moves.iter().any(|m| m.theirs == mv.theirs) {
+                        return Err(line.field_error(fields[2], "letter already used"));
+                    }
+                    if moves.iter().any(|m| m.ours == mv.ours) {
+                        return Err(line.field_error(fields[3], "letter already used"));
+                    }
+                    moves.push(mv);
+                }
+                Some(&"beats") => beats_lines.push(line),
+                Some(&"outcomes") => {
+                    if fields.len() != 4 {
+                        return Err(line.error("expected 'outcomes <loss> <draw> <win>'"));
+                    }
+                    for (score, field) in outcome_scores.iter_mut().zip(&fields[1..]) {
+                        *score = line.parse(field)?;
+                    }
+                }
+                Some(&"letters") => {
+                    if fields.len() != 4 {
+                        return Err(line.error("expected 'letters <loss> <draw> <win>'"));
+                    }
+                    for (idx, field) in fields[1..].iter().enumerate() {
+                        let c = letter(field)?;
+                        if outcome_letters[..idx].contains(&c) {
+                            return Err(line.field_error(field, "letter already used"));
+                        }
+                        outcome_letters[idx] = c;
+                    }
+                }
+                Some(first) => {
+                    return Err(line
+                        .field_error(first, "expected 'move', 'beats', 'outcomes' or 'letters'"))
+                }
             }
         }
-        // draw: copy opponent
-        3 => opponent_move,
-        // win: one ahead
-        6 => {
-            if opponent_move == 3 {
-                1
-            } else {
-                opponent_move + 1
+        if moves.len() < 3 || moves.len().is_multiple_of(2) {
+            return Err(Error::new(
+                "a game needs an odd number of moves, at least 3",
+            ));
+        }
+        let mut game = Self::cyclic(moves, outcome_scores);
+        game.outcome_letters = outcome_letters;
+        if beats_lines.is_empty() {
+            return Ok(game);
+        }
+
+        let count = game.moves.len();
+        let index = |line: &Line, name: &str| {
+            game.moves
+                .iter()
+                .position(|m| m.name == name)
+                .ok_or_else(|| line.field_error(name, "unknown move"))
+        };
+        let mut beats = vec![vec![false; count]; count];
+        for line in &beats_lines {
+            let mut names = line.text.split_whitespace().skip(1);
+            let winner = index(line, line.field(names.next(), "move")?)?;
+            for name in names {
+                let loser = index(line, name)?;
+                if loser == winner {
+                    return Err(line.field_error(name, "a move can't beat itself"));
+                }
+                beats[winner][loser] = true;
+            }
+        }
+        // Every pair of different moves must have a winner, and every
+        // move must have something to beat and lose to
+        for (a, row) in beats.iter().enumerate() {
+            if let Some(b) = (a + 1..count).find(|&b| row[b] == beats[b][a]) {
+                return Err(Error::new(format!(
+                    "exactly one of {} and {} must beat the other",
+                    game.moves[a].name, game.moves[b].name
+                )));
+            }
+            let wins = row.iter().filter(|&&b| b).count();
+            if wins == 0 || wins == count - 1 {
+                return Err(Error::new(format!(
+                    "{} must beat some moves and lose to others",
+                    game.moves[a].name
+                )));
             }
         }
-        _ => panic!("Invalid outcome"),
-    };
+        game.beats = beats;
+        Ok(game)
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The outcome a letter in the second column asks for, when it's read
+    /// as an outcome.
+    pub fn outcome_for(&self, letter: char) -> Option<Outcome> {
+        let idx = self.outcome_letters.iter().position(|&c| c == letter)?;
+        Some([Outcome::Loss, Outcome::Draw, Outcome::Win][idx])
+    }
+
+    /// The outcome for us, playing move `ours` against move `theirs`.
+    pub fn outcome(&self, ours: usize, theirs: usize) -> Outcome {
+        if self.beats[ours][theirs] {
+            Outcome::Win
+        } else if self.beats[theirs][ours] {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    /// Our score for a round, from our move and its outcome.
+    pub fn score(&self, ours: usize, theirs: usize) -> i32 {
+        let outcome_score = match self.outcome(ours, theirs) {
+            Outcome::Loss => self.outcome_scores[0],
+            Outcome::Draw => self.outcome_scores[1],
+            Outcome::Win => self.outcome_scores[2],
+        };
+        self.moves[ours].score + outcome_score
+    }
+
+    /// The highest scoring move with the given outcome against `theirs`.
+    pub fn play_for(&self, outcome: Outcome, theirs: usize) -> Option<usize> {
+        (0..self.moves.len())
+            .filter(|&ours| self.outcome(ours, theirs) == outcome)
+            .max_by_key(|&ours| self.score(ours, theirs))
+    }
+}
+
+/// One round of the strategy guide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    /// Index of the opponent's move.
+    pub theirs: usize,
+    /// The second column, whose meaning is the mystery.
+    pub column: char,
+    /// 1-based line number within the guide.
+    pub line: usize,
+}
+
+impl Round {
+    // An error pointing at the second column, for a reading it can't have
+    fn column_error(&self, game: &Game, message: String) -> Error {
+        let text = format!("{} {}", game.moves[self.theirs].theirs, self.column);
+        Error::new(message).at_line(self.line, &text).at_column(3)
+    }
+}
+
+#[derive(Debug)]
+pub struct Guide {
+    game: Game,
+    rounds: Vec<Round>,
+}
+
+// The second column is the move we play, if it's a move letter.
+fn our_move(game: &Game, round: &Round) -> Option<usize> {
+    game.moves.iter().position(|m| m.ours == round.column)
+}
+
+// The second column is the outcome we need: by default X to lose, Y to
+// draw and Z to win. None if it isn't an outcome letter.
+fn move_for_outcome(game: &Game, round: &Round) -> Option<usize> {
+    let desired_outcome = game.outcome_for(round.column)?;
+    let ours = game
+        .play_for(desired_outcome, round.theirs)
+        .expect("every move of a valid game can be beaten");
+    Some(ours)
+}
+
+// Letters for a message, such as "X, Y, Z".
+fn letter_list(letters: impl IntoIterator<Item = char>) -> String {
+    let letters: Vec<String> = letters.into_iter().map(|c| c.to_string()).collect();
+    letters.join(", ")
+}
+
+// The highest scoring move against the opponent's.
+fn best_move(game: &Game, round: &Round) -> usize {
+    (0..game.moves.len())
+        .max_by_key(|&ours| (game.score(ours, round.theirs), std::cmp::Reverse(ours)))
+        .unwrap()
+}
 
-    our_move + desired_outcome
+fn calc_score(game: &Game, round: &Round) -> Result<i32> {
+    let ours = our_move(game, round).ok_or_else(|| {
+        let letters = letter_list(game.moves.iter().map(|m| m.ours));
+        round.column_error(
+            game,
+            format!("second column must be a move, one of {}", letters),
+        )
+    })?;
+    Ok(game.score(ours, round.theirs))
 }
 
-pub fn step2() {
-    // Total score of all RPS rounds
-    let mut score = 0;
-    for line in read_list("inputs/day02.txt") {
-        score += calc_move_and_score(&line)
+fn calc_move_and_score(game: &Game, round: &Round) -> Result<i32> {
+    let ours = move_for_outcome(game, round).ok_or_else(|| {
+        let letters = letter_list(game.outcome_letters);
+        round.column_error(
+            game,
+            format!("second column must be an outcome, one of {}", letters),
+        )
+    })?;
+    Ok(game.score(ours, round.theirs))
+}
+
+// Total score and number of wins, draws and losses over the rounds.
+#[derive(Debug, Default)]
+struct Tally {
+    score: i32,
+    wins: usize,
+    draws: usize,
+    losses: usize,
+}
+
+impl Tally {
+    fn add(&mut self, game: &Game, ours: usize, theirs: usize) {
+        self.score += game.score(ours, theirs);
+        match game.outcome(ours, theirs) {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+    }
+}
+
+// Each round's move, outcome and score under both readings of the second
+// column and with the best move, then the totals for each. Readings the
+// column can't have are left blank.
+fn report(guide: &Guide) -> String {
+    let game = &guide.game;
+    let width = game.moves.iter().map(|m| m.name.len()).max().unwrap_or(0);
+    let width = width.max("Theirs".len());
+    // A move's name, outcome and score
+    let cell_width = width + 9;
+    let mut tallies: [Tally; 3] = Default::default();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:>5}  {:<w$}  {:<3}  {:<c$}  {:<c$}  Best move",
+        "Round",
+        "Theirs",
+        "Col",
+        "As a move",
+        "As an outcome",
+        w = width,
+        c = cell_width
+    )
+    .unwrap();
+    for (idx, round) in guide.rounds.iter().enumerate() {
+        let choices = [
+            our_move(game, round),
+            move_for_outcome(game, round),
+            Some(best_move(game, round)),
+        ];
+        let mut cells = vec![];
+        for (tally, ours) in tallies.iter_mut().zip(choices) {
+            let Some(ours) = ours else {
+                cells.push(String::new());
+                continue;
+            };
+            tally.add(game, ours, round.theirs);
+            cells.push(format!(
+                "{:<w$} {:<4} {:>3}",
+                game.moves[ours].name,
+                game.outcome(ours, round.theirs),
+                game.score(ours, round.theirs),
+                w = width
+            ));
+        }
+        let row = format!(
+            "{:>5}  {:<w$}  {:<3}  {:<c$}  {:<c$}  {}",
+            idx + 1,
+            game.moves[round.theirs].name,
+            round.column,
+            cells[0],
+            cells[1],
+            cells[2],
+            w = width,
+            c = cell_width
+        );
+        writeln!(out, "{}", row.trim_end()).unwrap();
+    }
+    for (label, tally) in ["As a move", "As an outcome", "Best moves"]
+        .iter()
+        .zip(&tallies)
+    {
+        writeln!(
+            out,
+            "{}: {} points; {} won, {} drawn, {} lost",
+            label, tally.score, tally.wins, tally.draws, tally.losses
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn check_round(game: &Game, line: &Line) -> Result<Round> {
+    let chars: Vec<char> = line.text.chars().collect();
+    if chars.len() != 3 || chars[1] != ' ' {
+        return Err(line.error("expected a round such as 'A Y'"));
+    }
+    let theirs = game.moves.iter().position(|m| m.theirs == chars[0]);
+    let theirs = theirs.ok_or_else(|| {
+        let letters = letter_list(game.moves.iter().map(|m| m.theirs));
+        line.char_error(0, format!("opponent move must be one of {}", letters))
+    })?;
+    // Each part checks the reading it needs, so a column only has to make
+    // sense as a move or an outcome
+    let mut columns: Vec<char> = game.moves.iter().map(|m| m.ours).collect();
+    for &c in &game.outcome_letters {
+        if !columns.contains(&c) {
+            columns.push(c);
+        }
+    }
+    if !columns.contains(&chars[2]) {
+        return Err(line.char_error(
+            2,
+            format!("second column must be one of {}", letter_list(columns)),
+        ));
+    }
+    Ok(Round {
+        theirs,
+        column: chars[2],
+        line: line.number,
+    })
+}
+
+// The built-in game of the given name, or else the rules in that file.
+fn load_game(rules: &str) -> Result<Game> {
+    match rules {
+        "rps" => Ok(Game::rock_paper_scissors()),
+        "rpsls" => Ok(Game::rock_paper_scissors_lizard_spock()),
+        path => {
+            let text = InputSource::File(PathBuf::from(path)).read("")?;
+            Game::from_rules(&text).map_err(|e| Error::new(format!("{}: {}", path, e)))
+        }
+    }
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Guide;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day02.txt");
+    const PARAMS: &'static [Param] = &[Param {
+        name: "rules",
+        default: "rps",
+        example: None,
+    }];
+    const REPORT: Option<fn(&Self::Input) -> String> = Some(report);
+
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        let game = load_game(&params.get::<String>("rules")?)?;
+        let mut rounds = vec![];
+        for line in numbered_lines(input)? {
+            rounds.push(check_round(&game, &line)?);
+        }
+        Ok(Guide { game, rounds })
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Total score of all RPS rounds
+        let mut score = 0;
+        for round in &input.rounds {
+            score += calc_score(&input.game, round)?;
+        }
+        Ok(score)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        // Total score of all RPS rounds
+        let mut score = 0;
+        for round in &input.rounds {
+            score += calc_move_and_score(&input.game, round)?;
+        }
+        Ok(score)
+    }
+
+    // `size` rounds
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut input = String::new();
+        for _ in 0..size.max(1) {
+            let theirs = rng.choose(&['A', 'B', 'C']);
+            let ours = rng.choose(&['X', 'Y', 'Z']);
+            input += &format!("{} {}\n", theirs, ours);
+        }
+        Generated::new(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day02::part1(&example_input::<Day02>()).unwrap(), 15);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day02::part2(&example_input::<Day02>()).unwrap(), 12);
+    }
+
+    #[test]
+    fn lizard_spock() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let index = |name: &str| game.moves().iter().position(|m| m.name == name).unwrap();
+        for (winner, loser) in [
+            ("Scissors", "Paper"),
+            ("Paper", "Rock"),
+            ("Rock", "Lizard"),
+            ("Lizard", "Spock"),
+            ("Spock", "Scissors"),
+            ("Scissors", "Lizard"),
+            ("Lizard", "Paper"),
+            ("Paper", "Spock"),
+            ("Spock", "Rock"),
+            ("Rock", "Scissors"),
+        ] {
+            assert_eq!(game.outcome(index(winner), index(loser)), Outcome::Win);
+            assert_eq!(game.outcome(index(loser), index(winner)), Outcome::Loss);
+        }
+        // Spock and Paper both beat Rock; Spock scores more
+        assert_eq!(
+            game.play_for(Outcome::Win, index("Rock")),
+            Some(index("Spock"))
+        );
+    }
+
+    #[test]
+    fn custom_rules() {
+        let rules = "# Rock-Paper-Scissors, scored differently\n\
+                     outcomes 0 1 2\n\
+                     move Rock A X 10\n\
+                     move Paper B Y 20\n\
+                     move Scissors C Z 30\n\
+                     beats Rock Scissors\n\
+                     beats Paper Rock\n\
+                     beats Scissors Paper\n";
+        let game = Game::from_rules(rules).unwrap();
+        assert_eq!(game.score(1, 0), 22);
+        assert_eq!(game.score(0, 1), 10);
+
+        let e = Game::from_rules(
+            "move Rock A X 1\nmove Paper B Y 2\nmove Scissors C Z 3\nbeats Rock Lizard\n",
+        )
+        .unwrap_err();
+        assert_eq!((e.line, e.column), (Some(4), Some(12)));
+        assert!(Game::from_rules("move A A X 1\nmove B B Y 1\n").is_err());
+        let lopsided = "move A A X 1\nmove B B Y 1\nmove C C Z 1\nbeats A B C\nbeats B C\n";
+        assert!(Game::from_rules(lopsided).is_err());
+    }
+
+    #[test]
+    fn lizard_spock_guide() {
+        let mut params = Params::new(Day02::PARAMS, false);
+        params.set("rules", "rpsls").unwrap();
+        // Spock beats Rock, Scissors beats Paper, and Lizard draws
+        let guide = Day02::parse("A Y\nB X\nE Z\n", &params).unwrap();
+        assert_eq!(Day02::part1(&guide).unwrap(), 27);
+        // Rock draws, Spock loses to Paper, and Scissors beats Lizard
+        assert_eq!(Day02::part2(&guide).unwrap(), 17);
+        // V is only a move, so the guide can't be read as outcomes
+        let guide = Day02::parse("B Y\nA V\n", &params).unwrap();
+        assert_eq!(Day02::part1(&guide).unwrap(), 8);
+        let e = Day02::part2(&guide).unwrap_err();
+        assert_eq!((e.line, e.column), (Some(2), Some(3)));
+        assert!(Day02::parse("A U\n", &params).is_err());
+    }
+
+    #[test]
+    fn report_example() {
+        let report = report(&example_input::<Day02>());
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[1],
+            "    1  Rock      Y    Paper    win    8  Rock     draw   4  Paper    win    8"
+        );
+        assert_eq!(lines[4], "As a move: 15 points; 1 won, 1 drawn, 1 lost");
+        assert_eq!(lines[5], "As an outcome: 12 points; 1 won, 1 drawn, 1 lost");
+        assert_eq!(lines[6], "Best moves: 24 points; 3 won, 0 drawn, 0 lost");
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day02>(2);
+        assert_eq!(Day02::part1(&input).unwrap(), 12645);
+        assert_eq!(Day02::part2(&input).unwrap(), 11756);
     }
-    println!("Total score: {}", score);
 }
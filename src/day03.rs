@@ -1,14 +1,11 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::{numbered_lines, Line},
+    params::{Param, Params},
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
 fn item_value(x: char) -> u8 {
     match x {
         'a'..='z' => x as u8 - b'a' + 1,
@@ -17,51 +14,291 @@ fn item_value(x: char) -> u8 {
     }
 }
 
-fn make_set(compartment: &str) -> HashSet<u8> {
-    compartment.chars().map(item_value).collect()
+fn item_char(value: u8) -> char {
+    match value {
+        1..=26 => (b'a' + value - 1) as char,
+        _ => (b'A' + value - 27) as char,
+    }
 }
 
-fn joint_item_value(line: &str) -> i32 {
-    let compartment1 = &line[0..(line.len() / 2)];
-    let compartment2 = &line[(line.len() / 2)..];
+/// A set of items, as a mask with bit `n - 1` set for the item of
+/// priority `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Items(u64);
+
+impl Items {
+    /// Every item, so a starting point for finding those in common.
+    pub const ALL: Items = Items((1 << 52) - 1);
 
-    let c1 = make_set(compartment1);
-    let c2 = make_set(compartment2);
+    /// The items in a string of letters.
+    pub fn new(items: &str) -> Self {
+        Items(
+            items
+                .chars()
+                .map(item_value)
+                .filter(|&value| value > 0)
+                .fold(0, |mask, value| mask | 1 << (value - 1)),
+        )
+    }
+
+    pub fn intersection(self, other: Items) -> Items {
+        Items(self.0 & other.0)
+    }
 
-    let mut joint_items = c1.intersection(&c2);
-    let item = joint_items.next();
-    *item.unwrap() as i32
+    /// Priorities of the items, lowest first.
+    pub fn values(self) -> impl Iterator<Item = u8> {
+        (1..=52).filter(move |value| self.0 & 1 << (value - 1) != 0)
+    }
+
+    /// The priority of the only item, or an error describing them.
+    pub fn single(self) -> std::result::Result<u8, String> {
+        let items: String = self.values().map(item_char).collect();
+        match items.len() {
+            0 => Err("no item in common".to_string()),
+            1 => Ok(self.0.trailing_zeros() as u8 + 1),
+            _ => Err(format!("several items in common: {}", items)),
+        }
+    }
 }
 
-pub fn step1() {
-    // Total priority of items in both compartments of each rucksack
-    let mut score = 0;
-    for line in read_list("inputs/day03.txt") {
-        score += joint_item_value(&line)
+/// The items in all of the sets.
+pub fn shared_items<'a>(sets: impl IntoIterator<Item = &'a Items>) -> Items {
+    sets.into_iter()
+        .fold(Items::ALL, |shared, items| shared.intersection(*items))
+}
+
+#[derive(Debug, Clone)]
+pub struct Rucksack {
+    compartments: Vec<Items>,
+}
+
+impl Rucksack {
+    fn new(line: &Line, compartment_count: usize) -> Result<Self> {
+        if let Some(idx) = line.text.find(|c: char| !c.is_ascii_alphabetic()) {
+            return Err(line.char_error(idx, "items must be letters a-z or A-Z"));
+        }
+        if !line.text.len().is_multiple_of(compartment_count) {
+            return Err(line.error(format!(
+                "rucksack must split into {} equal compartments",
+                compartment_count
+            )));
+        }
+        let size = line.text.len() / compartment_count;
+        let compartments = (0..compartment_count)
+            .map(|idx| Items::new(&line.text[idx * size..(idx + 1) * size]))
+            .collect();
+        Ok(Self { compartments })
+    }
+
+    /// Items packed into every compartment.
+    pub fn misplaced(&self) -> Items {
+        shared_items(&self.compartments)
+    }
+
+    pub fn items(&self) -> Items {
+        Items(
+            self.compartments
+                .iter()
+                .fold(0, |mask, items| mask | items.0),
+        )
+    }
+}
+
+/// Every rucksack, in groups of elves which share a badge.
+pub struct Rucksacks {
+    rucksacks: Vec<Rucksack>,
+    group_size: usize,
+}
+
+impl Rucksacks {
+    pub fn groups(&self) -> impl Iterator<Item = &[Rucksack]> {
+        self.rucksacks.chunks(self.group_size)
     }
-    println!("Total of item priorities: {}", score);
 }
 
-fn find_common_item(group: &[String]) -> char {
-    // find char which is in all of the entries
-    assert!(group.len() == 3);
-    let mut common: HashSet<_> = group[0].chars().collect();
-    for elf in &group[1..] {
-        let elf_items: HashSet<_> = elf.chars().collect();
-        // RUST: took me a long time to find the '.copied()' method here...
-        common = elf_items.intersection(&common).copied().collect();
-    }
-    assert!(common.len() == 1);
-    *common.iter().next().unwrap()
+// The badge of a group: the only item every rucksack has.
+fn badge(group: &[Rucksack]) -> Items {
+    shared_items(&group.iter().map(Rucksack::items).collect::<Vec<_>>())
 }
 
-pub fn step2() {
-    // Total priority of badges across each group of three elves
-    let mut score: i32 = 0;
-    for group in read_list("inputs/day03.txt").chunks(3) {
-        let elf_group = group;
-        let badge_value = find_common_item(elf_group);
-        score += item_value(badge_value) as i32;
+// Three rucksacks whose compartments share one item each, and which
+// together share only their badge.
+fn generate_group(rng: &mut Rng) -> Vec<String> {
+    let mut items: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+    rng.shuffle(&mut items);
+    let badge = items[0];
+    let mut group = vec![];
+    // Otherwise each rucksack has its own items, so nothing else is common
+    for pool in items[1..].chunks(17) {
+        let shared = if rng.chance(25) { badge } else { pool[0] };
+        let rest: Vec<char> = pool.iter().copied().filter(|&c| c != shared).collect();
+        let (left_pool, right_pool) = rest.split_at(rest.len() / 2);
+        let len = rng.range(3..=16) as usize;
+        let mut left = vec![shared];
+        let mut right = vec![shared];
+        if shared != badge {
+            if rng.chance(50) {
+                left.push(badge);
+            } else {
+                right.push(badge);
+            }
+        }
+        while left.len() < len {
+            left.push(*rng.choose(left_pool));
+        }
+        while right.len() < len {
+            right.push(*rng.choose(right_pool));
+        }
+        rng.shuffle(&mut left);
+        rng.shuffle(&mut right);
+        group.push(left.iter().chain(&right).collect());
+    }
+    group
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Rucksacks;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day03.txt");
+    const PARAMS: &'static [Param] = &[
+        Param {
+            name: "compartments",
+            default: "2",
+            example: None,
+        },
+        Param {
+            name: "group_size",
+            default: "3",
+            example: None,
+        },
+    ];
+
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        let compartment_count: usize = params.get("compartments")?;
+        let group_size: usize = params.get("group_size")?;
+        if compartment_count == 0 || group_size == 0 {
+            return Err(Error::new("compartments and group_size must be positive"));
+        }
+        let lines = numbered_lines(input)?;
+        let mut rucksacks = vec![];
+        for line in &lines {
+            let rucksack = Rucksack::new(line, compartment_count)?;
+            rucksack
+                .misplaced()
+                .single()
+                .map_err(|e| line.error(format!("compartments have {}", e)))?;
+            rucksacks.push(rucksack);
+        }
+        if !rucksacks.len().is_multiple_of(group_size) {
+            return Err(Error::new(format!(
+                "{} rucksacks don't make whole groups of {}",
+                rucksacks.len(),
+                group_size
+            )));
+        }
+        let input = Rucksacks {
+            rucksacks,
+            group_size,
+        };
+        for (group, first) in input.groups().zip(lines.iter().step_by(group_size)) {
+            badge(group)
+                .single()
+                .map_err(|e| first.error(format!("group starting here has {}", e)))?;
+        }
+        Ok(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Total priority of items in every compartment of each rucksack
+        let mut score = 0;
+        for rucksack in &input.rucksacks {
+            // Each has a single item, checked when parsing
+            score += rucksack.misplaced().single().unwrap() as i32;
+        }
+        Ok(score)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        // Total priority of badges across each group of elves
+        let mut score: i32 = 0;
+        for group in input.groups() {
+            score += badge(group).single().unwrap() as i32;
+        }
+        Ok(score)
+    }
+
+    // `size` rucksacks, rounded up to whole groups
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut rucksacks = vec![];
+        for _ in 0..size.div_ceil(3).max(1) {
+            rucksacks.extend(generate_group(rng));
+        }
+        Generated::new(rucksacks.join("\n") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day03::part1(&example_input::<Day03>()).unwrap(), 157);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day03::part2(&example_input::<Day03>()).unwrap(), 70);
+    }
+
+    #[test]
+    fn items_as_masks() {
+        let items = Items::new("vJrwpWtwJgWr");
+        assert_eq!(
+            items.values().map(item_char).collect::<String>(),
+            "gprtvwJW"
+        );
+        let shared = shared_items(&[Items::new("abcZ"), Items::new("bZq")]);
+        assert_eq!(shared.values().collect::<Vec<_>>(), [2, 52]);
+        assert_eq!(
+            shared.single(),
+            Err("several items in common: bZ".to_string())
+        );
+        assert_eq!(Items::new("Z").single(), Ok(52));
+        assert!(Items::new("").single().is_err());
+    }
+
+    #[test]
+    fn other_shapes() {
+        let mut params = Params::new(Day03::PARAMS, false);
+        params.set("compartments", "3").unwrap();
+        params.set("group_size", "2").unwrap();
+        let input = Day03::parse("abcadeafg\nbxzbyybqr\n", &params).unwrap();
+        assert_eq!(Day03::part1(&input).unwrap(), 1 + 2);
+        assert_eq!(Day03::part2(&input).unwrap(), 2);
+    }
+
+    #[test]
+    fn reports_ambiguous_items() {
+        let params = Params::new(Day03::PARAMS, false);
+        let e = Day03::parse("abab\n", &params).err().unwrap();
+        assert_eq!(e.line, Some(1));
+        assert!(e.message.contains("several items in common: ab"));
+        let e = Day03::parse("aa\nbb\ncc\n", &params).err().unwrap();
+        assert_eq!(e.line, Some(1));
+        assert_eq!(e.message, "group starting here has no item in common");
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day03>(3);
+        assert_eq!(Day03::part1(&input).unwrap(), 8252);
+        assert_eq!(Day03::part2(&input).unwrap(), 2828);
     }
-    println!("Total priority of badges across groups: {}", score);
 }
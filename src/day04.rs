@@ -1,19 +1,18 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
+use std::collections::HashSet;
+
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    input::{numbered_lines, Line},
+    params::Params,
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+// Section IDs from the start of an elf's assignment to its end
+type Assignment = (i32, i32);
 
-fn task_range(task_spec: &str) -> HashSet<i32> {
-    // Convert "4-7" into a set of {4,5,6,7}
-    let mut task_spec_split = task_spec.split('-');
-    let start: i32 = task_spec_split.next().unwrap().parse().unwrap();
-    let end: i32 = task_spec_split.next().unwrap().parse().unwrap();
+fn task_range(&(start, end): &Assignment) -> HashSet<i32> {
+    // Convert 4-7 into a set of {4,5,6,7}
     // RUST: I suspect there's a better way of doing this?
     let mut result = HashSet::<i32>::new();
     for value in start..=end {
@@ -22,42 +21,109 @@ fn task_range(task_spec: &str) -> HashSet<i32> {
     result
 }
 
-fn full_overlap(a: &str, b: &str) -> bool {
+fn parse_pair(line: &Line) -> Result<(Assignment, Assignment)> {
+    // "2-4,6-8": two ranges separated by a comma
+    let specs: Vec<&str> = line.text.split(',').collect();
+    if specs.len() != 2 {
+        return Err(line.error("expected two ranges separated by ','"));
+    }
+    let mut pair = vec![];
+    for spec in specs {
+        let (start, end) = spec
+            .split_once('-')
+            .ok_or_else(|| line.field_error(spec, "expected a range such as '2-4'"))?;
+        let start: i32 = line.parse(start)?;
+        let end: i32 = line.parse(end)?;
+        if start > end {
+            return Err(line.field_error(spec, "range start is after its end"));
+        }
+        pair.push((start, end));
+    }
+    Ok((pair[0], pair[1]))
+}
+
+fn full_overlap(a: &Assignment, b: &Assignment) -> bool {
     let a_set = task_range(a);
     let b_set = task_range(b);
     a_set.is_superset(&b_set) || b_set.is_superset(&a_set)
 }
 
-pub fn step1() {
-    // Number of elf pairs with fully overlapping task spec
-    let mut count = 0;
-    for line in read_list("inputs/day04.txt") {
-        let mut elf_tasks = line.split(',');
-        let elf1_task_spec = elf_tasks.next().unwrap();
-        let elf2_task_spec = elf_tasks.next().unwrap();
-        if full_overlap(elf1_task_spec, elf2_task_spec) {
-            count += 1;
-        }
-    }
-    println!("Count of fully overlapping elf-pair tasks: {}", count);
-}
-
-fn any_overlap(a: &str, b: &str) -> bool {
+fn any_overlap(a: &Assignment, b: &Assignment) -> bool {
     let a_set = task_range(a);
     let b_set = task_range(b);
     !a_set.is_disjoint(&b_set)
 }
 
-pub fn step2() {
-    // Number of elf pairs with any overlap in task spec
-    let mut count = 0;
-    for line in read_list("inputs/day04.txt") {
-        let mut elf_tasks = line.split(',');
-        let elf1_task_spec = elf_tasks.next().unwrap();
-        let elf2_task_spec = elf_tasks.next().unwrap();
-        if any_overlap(elf1_task_spec, elf2_task_spec) {
-            count += 1;
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Vec<(Assignment, Assignment)>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day04.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        numbered_lines(input)?.iter().map(parse_pair).collect()
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Number of elf pairs with fully overlapping task spec
+        let mut count = 0;
+        for (elf1_tasks, elf2_tasks) in input {
+            if full_overlap(elf1_tasks, elf2_tasks) {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        // Number of elf pairs with any overlap in task spec
+        let mut count = 0;
+        for (elf1_tasks, elf2_tasks) in input {
+            if any_overlap(elf1_tasks, elf2_tasks) {
+                count += 1;
+            }
         }
+        Ok(count)
+    }
+
+    // `size` pairs of elves
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut input = String::new();
+        for _ in 0..size.max(1) {
+            let mut sections = || {
+                let start = rng.range(1..=99);
+                format!("{}-{}", start, rng.range(start..=99))
+            };
+            let first = sections();
+            input += &format!("{},{}\n", first, sections());
+        }
+        Generated::new(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day04::part1(&example_input::<Day04>()).unwrap(), 2);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day04::part2(&example_input::<Day04>()).unwrap(), 4);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day04>(4);
+        assert_eq!(Day04::part1(&input).unwrap(), 599);
+        assert_eq!(Day04::part2(&input).unwrap(), 928);
     }
-    println!("Count of overlapping elf-pair tasks: {}", count);
 }
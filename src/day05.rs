@@ -1,90 +1,234 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
+use std::collections::HashMap;
+
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::Params,
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
+#[derive(Debug)]
+pub struct Stacks {
+    stack: HashMap<i32, Vec<char>>,
+    rules: Vec<(i32, i32, i32)>,
 }
 
-pub fn step1() {
-    let mut rules: Vec<(i32, i32, i32)> = vec![];
-    let mut stack: HashMap<i32, Vec<char>> = HashMap::new();
-    for line in read_list("inputs/day05.txt") {
-        if line.starts_with('[') {
-            for entry in 0..9 {
-                if let Some(c) = line.chars().nth(1 + entry * 4) {
+impl Stacks {
+    fn new(input: &str) -> Result<Self> {
+        let mut rules: Vec<(i32, i32, i32)> = vec![];
+        let mut stack: HashMap<i32, Vec<char>> = HashMap::new();
+        // Crates on each stack as the moves are applied, to catch moves
+        // which would take from an empty stack.
+        let mut heights: HashMap<i32, i32> = HashMap::new();
+        // Stack ids come from the numbering line, as stacks may start empty
+        let mut numbered = false;
+        for line in numbered_lines(input)? {
+            // Lower stacks may be empty, so crate lines can start with spaces
+            if line.text.contains('[') {
+                if numbered {
+                    return Err(line.error("crates below the stack numbering"));
+                }
+                for (column, c) in line.text.chars().enumerate().skip(1).step_by(4) {
                     if c == ' ' {
                         continue;
                     };
-                    let v: &mut Vec<char> = stack.entry(1 + entry as i32).or_default();
+                    if !c.is_ascii_uppercase() {
+                        return Err(line.char_error(column, "invalid crate"));
+                    }
+                    let v: &mut Vec<char> = stack.entry(1 + column as i32 / 4).or_default();
                     v.insert(0, c);
                 }
+            } else if line
+                .text
+                .trim_start()
+                .starts_with(|c: char| c.is_ascii_digit())
+            {
+                if numbered {
+                    return Err(line.error("stacks already numbered"));
+                }
+                let words: Vec<&str> = line.text.split_whitespace().collect();
+                for (i, word) in words.iter().enumerate() {
+                    let id: i32 = line.parse(word)?;
+                    if id != 1 + i as i32 {
+                        return Err(line.field_error(word, "stacks must be numbered 1, 2, 3, ..."));
+                    }
+                    stack.entry(id).or_default();
+                }
+                if let Some(id) = stack.keys().find(|&&id| id > words.len() as i32) {
+                    return Err(line.error(format!("no number for stack {}", id)));
+                }
+                for (id, crates) in &stack {
+                    heights.insert(*id, crates.len() as i32);
+                }
+                numbered = true;
+            } else if line.text.starts_with("move") {
+                if !numbered {
+                    return Err(line.error("moves before the stack numbering"));
+                }
+                let words: Vec<&str> = line.text.split(' ').collect();
+                if words.len() != 6 || words[2] != "from" || words[4] != "to" {
+                    return Err(line.error("expected 'move <count> from <stack> to <stack>'"));
+                }
+                // extract elements 1, 3, 5
+                let count: i32 = line.parse(words[1])?;
+                let source: i32 = line.parse(words[3])?;
+                let dest: i32 = line.parse(words[5])?;
+                for (id, word) in [(source, words[3]), (dest, words[5])] {
+                    if !heights.contains_key(&id) {
+                        return Err(line.field_error(word, format!("no stack {}", id)));
+                    }
+                }
+                if heights[&source] < count {
+                    return Err(line.field_error(
+                        words[1],
+                        format!("stack {} only has {} crates", source, heights[&source]),
+                    ));
+                }
+                *heights.get_mut(&source).unwrap() -= count;
+                *heights.get_mut(&dest).unwrap() += count;
+                rules.push((count, source, dest));
+            } else if !line.text.is_empty() {
+                // Anything else should be the blank separator
+                return Err(line.error("unexpected line"));
             }
-        } else if line.starts_with("move") {
-            let mut line_iter = line.split(' ');
-            // extract elements 1, 3, 5 - note nth() consumes, so we're just skipping one
-            // each time to get these.
-            let count = line_iter.nth(1).unwrap().parse::<i32>().unwrap();
-            let source = line_iter.nth(1).unwrap().parse::<i32>().unwrap();
-            let dest = line_iter.nth(1).unwrap().parse::<i32>().unwrap();
-            rules.push((count, source, dest));
         }
-    }
-    for (count, source, dest) in rules {
-        for _ in 0..count {
-            let source_stack: &mut Vec<char> = stack.get_mut(&(source as i32)).unwrap();
-            let element = source_stack.pop().unwrap();
-            let dest_stack: &mut Vec<char> = stack.get_mut(&(dest as i32)).unwrap();
-            dest_stack.push(element);
+        if !numbered {
+            return Err(Error::new("no line numbering the stacks"));
         }
+        Ok(Self { stack, rules })
     }
-    for entry in 1..=9 {
-        print!("{}", stack.get(&entry).unwrap().last().unwrap());
+
+    // The top crate of each stack, skipping those left empty
+    fn tops(stack: &HashMap<i32, Vec<char>>) -> String {
+        let mut result = String::new();
+        for entry in 1..=stack.len() as i32 {
+            if let Some(&top) = stack.get(&entry).unwrap().last() {
+                result.push(top);
+            }
+        }
+        result
     }
-    println!();
 }
 
-pub fn step2() {
-    let mut rules: Vec<(i32, i32, i32)> = vec![];
-    let mut stack: HashMap<i32, Vec<char>> = HashMap::new();
-    for line in read_list("inputs/day05.txt") {
-        if line.starts_with('[') {
-            for entry in 0..9 {
-                if let Some(c) = line.chars().nth(1 + entry * 4) {
-                    if c == ' ' {
-                        continue;
-                    };
-                    let v: &mut Vec<char> = stack.entry(1 + entry as i32).or_default();
-                    v.insert(0, c);
-                }
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Stacks;
+    type Answer1 = String;
+    type Answer2 = String;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day05.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        Stacks::new(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<String> {
+        let mut stack = input.stack.clone();
+        for &(count, source, dest) in &input.rules {
+            for _ in 0..count {
+                let source_stack: &mut Vec<char> = stack.get_mut(&source).unwrap();
+                let element = source_stack.pop().unwrap();
+                let dest_stack: &mut Vec<char> = stack.get_mut(&dest).unwrap();
+                dest_stack.push(element);
             }
-        } else if line.starts_with("move") {
-            let mut line_iter = line.split(' ');
-            // extract elements 1, 3, 5 - note nth() consumes, so we're just skipping one
-            // each time to get these.
-            let count = line_iter.nth(1).unwrap().parse::<i32>().unwrap();
-            let source = line_iter.nth(1).unwrap().parse::<i32>().unwrap();
-            let dest = line_iter.nth(1).unwrap().parse::<i32>().unwrap();
-            rules.push((count, source, dest));
         }
+        Ok(Stacks::tops(&stack))
     }
-    for (count, source, dest) in rules {
-        let mut elements: Vec<char> = vec![];
-        let source_stack: &mut Vec<char> = stack.get_mut(&(source as i32)).unwrap();
-        for _ in 0..count {
-            let element = source_stack.pop().unwrap();
-            elements.push(element);
+
+    fn part2(input: &Self::Input) -> Result<String> {
+        let mut stack = input.stack.clone();
+        for &(count, source, dest) in &input.rules {
+            let mut elements: Vec<char> = vec![];
+            let source_stack: &mut Vec<char> = stack.get_mut(&source).unwrap();
+            for _ in 0..count {
+                let element = source_stack.pop().unwrap();
+                elements.push(element);
+            }
+            let dest_stack: &mut Vec<char> = stack.get_mut(&dest).unwrap();
+            while let Some(element) = elements.pop() {
+                dest_stack.push(element);
+            }
         }
-        let dest_stack: &mut Vec<char> = stack.get_mut(&(dest as i32)).unwrap();
-        while !elements.is_empty() {
-            dest_stack.push(elements.pop().unwrap());
+        Ok(Stacks::tops(&stack))
+    }
+
+    // Nine stacks, with `size` moves between them which never empty one
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut heights: Vec<i64> = (0..9).map(|_| rng.range(1..=8)).collect();
+        heights[rng.below(9)] = 8;
+        let mut input = String::new();
+        for level in (0..8).rev() {
+            let crates: Vec<String> = heights
+                .iter()
+                .map(|&height| match height > level {
+                    true => format!("[{}]", (b'A' + rng.below(26) as u8) as char),
+                    false => "   ".to_string(),
+                })
+                .collect();
+            input += &(crates.join(" ") + "\n");
         }
+        let labels: Vec<String> = (1..=9).map(|id| format!(" {} ", id)).collect();
+        input += &(labels.join(" ") + "\n\n");
+        for _ in 0..size.max(1) {
+            let sources: Vec<usize> = (0..9).filter(|&id| heights[id] > 1).collect();
+            let source = *rng.choose(&sources);
+            let mut dest = rng.below(8);
+            if dest >= source {
+                dest += 1;
+            }
+            let count = rng.range(1..=(heights[source] - 1).min(10));
+            heights[source] -= count;
+            heights[dest] += count;
+            input += &format!("move {} from {} to {}\n", count, source + 1, dest + 1);
+        }
+        Generated::new(input)
     }
-    for entry in 1..=9 {
-        print!("{}", stack.get(&entry).unwrap().last().unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day05::part1(&example_input::<Day05>()).unwrap(), "CMZ");
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day05::part2(&example_input::<Day05>()).unwrap(), "MCD");
+    }
+
+    #[test]
+    fn emptied_stack() {
+        let input = "    [D]\n[N] [C]\n 1   2\n\nmove 1 from 1 to 2\n";
+        let stacks = Day05::parse(input, &Params::new(&[], false)).unwrap();
+        assert_eq!(Day05::part1(&stacks).unwrap(), "N");
+        assert_eq!(Day05::part2(&stacks).unwrap(), "N");
+    }
+
+    #[test]
+    fn empty_stacks() {
+        let params = Params::new(&[], false);
+        let input = "    [D]    \n[N] [C]    \n 1   2   3\n\nmove 1 from 1 to 3\n";
+        let stacks = Day05::parse(input, &params).unwrap();
+        assert_eq!(Day05::part1(&stacks).unwrap(), "DN");
+        // Empty in the middle
+        let input = "[D]        \n[N]     [C]\n 1   2   3\n\nmove 1 from 1 to 2\n";
+        let stacks = Day05::parse(input, &params).unwrap();
+        assert_eq!(Day05::part1(&stacks).unwrap(), "NDC");
+        let e = Day05::parse("[A]\n 1\n\nmove 1 from 1 to 2\n", &params).unwrap_err();
+        assert_eq!((e.line, e.column), (Some(4), Some(18)));
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day05>(5);
+        assert_eq!(Day05::part1(&input).unwrap(), "QNHWJVJZW");
+        assert_eq!(Day05::part2(&input).unwrap(), "BPCZJLFJW");
     }
-    println!();
 }
@@ -1,42 +1,92 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufReader, Read},
-};
+use std::collections::HashSet;
 
-pub fn read_chars(filename: &str) -> Vec<char> {
-    let f = File::open(filename).expect("Could not read file");
-    let mut buffer = String::new();
-    BufReader::new(f).read_to_string(&mut buffer).unwrap();
-    return buffer.chars().collect();
-}
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::read_chars,
+    params::Params,
+    solution::Solution,
+};
 
-pub fn step1() {
-    let stream = read_chars("inputs/day06.txt");
-    for start_pos in 4..=stream.len() {
+/// Position just after the first `marker_len` distinct characters.
+pub fn marker_position(stream: &[char], marker_len: usize) -> Result<usize> {
+    for start_pos in marker_len..=stream.len() {
         let mut marker_chars = HashSet::new();
-        for offset in 1..=4 {
-        marker_chars.insert(stream[start_pos - offset]);
+        for offset in 1..=marker_len {
+            marker_chars.insert(stream[start_pos - offset]);
         }
 
-        if marker_chars.len() == 4 {
-            println!("Packet found at position {}", start_pos);
-            break;
+        if marker_chars.len() == marker_len {
+            return Ok(start_pos);
         }
     }
+    Err(Error::new(format!(
+        "no marker of {} distinct characters",
+        marker_len
+    )))
 }
 
-pub fn step2() {
-    let stream = read_chars("inputs/day06.txt");
-    for start_pos in 14..=stream.len() {
-        let mut marker_chars = HashSet::new();
-        for offset in 1..=14 {
-            marker_chars.insert(stream[start_pos - offset]);
-        }
+pub struct Day06;
 
-        if marker_chars.len() == 14 {
-            println!("Message found at position {}", start_pos);
-            break;
-        }
+impl Solution for Day06 {
+    type Input = Vec<char>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day06.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        read_chars(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<usize> {
+        // Start-of-packet marker
+        marker_position(input, 4)
+    }
+
+    fn part2(input: &Self::Input) -> Result<usize> {
+        // Start-of-message marker
+        marker_position(input, 14)
     }
-}
\ No newline at end of file
+
+    // About `size` characters before the start-of-message marker
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        // Too few letters for a start-of-message marker until the end
+        let letters: Vec<char> = ('a'..='m').collect();
+        let mut stream: String = (0..size).map(|_| *rng.choose(&letters)).collect();
+        let mut marker: Vec<char> = ('a'..='z').collect();
+        rng.shuffle(&mut marker);
+        stream.extend(&marker[..14]);
+        Generated::new(stream + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day06::part1(&example_input::<Day06>()).unwrap(), 7);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day06::part2(&example_input::<Day06>()).unwrap(), 19);
+    }
+
+    #[test]
+    fn no_marker() {
+        let stream = Day06::parse("abca\n", &Params::new(Day06::PARAMS, false)).unwrap();
+        assert!(Day06::part1(&stream).is_err());
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day06>(6);
+        assert_eq!(Day06::part1(&input).unwrap(), 1850);
+        assert_eq!(Day06::part2(&input).unwrap(), 2823);
+    }
+}
@@ -1,28 +1,28 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::collections::{HashMap, HashSet};
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::{Param, Params},
+    solution::Solution,
+};
 
-pub fn step1() {
+/// Total size of each directory, keyed by absolute path.
+pub fn directory_sizes(input: &str) -> Result<HashMap<String, usize>> {
     let mut dir_stack: Vec<String> = vec![];
     let mut sizes: HashMap<String, usize> = HashMap::new();
-    for line in read_list("inputs/day07.txt") {
-        let parts: Vec<&str> = line.split_whitespace().collect();
+    for line in numbered_lines(input)? {
+        let parts: Vec<&str> = line.text.split_whitespace().collect();
         match parts.len() {
             2 => match parts[0] {
                 "dir" => (),
                 "$" => match parts[1] {
                     "ls" => (),
-                    _ => panic!("Unknown command {}", parts[1]),
+                    _ => return Err(line.field_error(parts[1], "unknown command")),
                 },
                 _ => {
-                    let size: usize = parts[0].parse().unwrap();
+                    let size: usize = line.parse(parts[0])?;
                     let _fname = parts[1];
 
                     let mut pwd_stack = dir_stack.clone();
@@ -43,77 +43,172 @@ pub fn step1() {
                         }
                         _ => dir_stack.push(parts[2].to_string()),
                     },
-                    _ => panic!("Unknown command {}", parts[1]),
+                    _ => return Err(line.field_error(parts[1], "unknown command")),
                 },
-                _ => panic!("Unknown entry {:?}", parts),
+                _ => return Err(line.error("unknown entry")),
             },
-            _ => panic!("Unknown entry: {:?}", parts),
+            _ => return Err(line.error("unknown entry")),
+        }
+    }
+
+    Ok(sizes)
+}
+
+pub struct Filesystem {
+    sizes: HashMap<String, usize>,
+    disk_size: usize,
+    // Free space the update needs
+    needed: usize,
+}
+
+#[derive(Default)]
+struct Dir {
+    files: Vec<(String, i64)>,
+    dirs: Vec<(String, Dir)>,
+}
+
+impl Dir {
+    // A directory with `count` more directories somewhere below it.
+    fn random(rng: &mut Rng, count: usize) -> Self {
+        let mut dir = Dir::default();
+        let mut names = HashSet::new();
+        let mut name = |rng: &mut Rng| loop {
+            let name: String = (0..rng.range(1..=8))
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect();
+            if names.insert(name.clone()) {
+                return name;
+            }
+        };
+        for _ in 0..rng.below(5) {
+            let file = format!("{}.{}", name(rng), rng.choose(&["txt", "dat", "log"]));
+            dir.files.push((file, rng.range(1..=300000)));
         }
+        let mut remaining = count;
+        while remaining > 0 {
+            let below = rng.below(remaining);
+            let sub = name(rng);
+            dir.dirs.push((sub, Dir::random(rng, below)));
+            remaining -= below + 1;
+        }
+        dir
+    }
+
+    fn size(&self) -> i64 {
+        let files: i64 = self.files.iter().map(|(_, size)| size).sum();
+        files + self.dirs.iter().map(|(_, dir)| dir.size()).sum::<i64>()
     }
 
-    let mut total = 0;
-    for value in sizes.values() {
-        if *value <= 100000 {
-            total += value;
+    // The commands and output exploring this directory depth first.
+    fn explore(&self, out: &mut String) {
+        *out += "$ ls\n";
+        for (name, _) in &self.dirs {
+            *out += &format!("dir {}\n", name);
+        }
+        for (name, size) in &self.files {
+            *out += &format!("{} {}\n", size, name);
+        }
+        for (name, dir) in &self.dirs {
+            *out += &format!("$ cd {}\n", name);
+            dir.explore(out);
+            *out += "$ cd ..\n";
         }
     }
-    println!("Total size of directories < 100000 is {}", total);
 }
 
-pub fn step2() {
-    let mut dir_stack: Vec<String> = vec![];
-    let mut sizes: HashMap<String, usize> = HashMap::new();
-    for line in read_list("inputs/day07.txt") {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        match parts.len() {
-            2 => match parts[0] {
-                "dir" => (),
-                "$" => match parts[1] {
-                    "ls" => (),
-                    _ => panic!("Unknown command {}", parts[1]),
-                },
-                _ => {
-                    let size: usize = parts[0].parse().unwrap();
-                    let _fname = parts[1];
+pub struct Day07;
 
-                    let mut pwd_stack = dir_stack.clone();
-                    while !pwd_stack.is_empty() {
-                        let path = "/".to_string() + &pwd_stack.join("/");
-                        *sizes.entry(path).or_insert(0) += size;
-                        pwd_stack.pop();
-                    }
-                    *sizes.entry("/".to_string()).or_insert(0) += size;
-                }
-            },
-            3 => match parts[0] {
-                "$" => match parts[1] {
-                    "cd" => match parts[2] {
-                        "/" => dir_stack.clear(),
-                        ".." => {
-                            dir_stack.pop();
-                        }
-                        _ => dir_stack.push(parts[2].to_string()),
-                    },
-                    _ => panic!("Unknown command {}", parts[1]),
-                },
-                _ => panic!("Unknown entry {:?}", parts),
-            },
-            _ => panic!("Unknown entry: {:?}", parts),
+impl Solution for Day07 {
+    type Input = Filesystem;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day07.txt");
+    const PARAMS: &'static [Param] = &[
+        Param {
+            name: "disk_size",
+            default: "70000000",
+            example: None,
+        },
+        Param {
+            name: "needed",
+            default: "30000000",
+            example: None,
+        },
+    ];
+
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        Ok(Filesystem {
+            sizes: directory_sizes(input)?,
+            disk_size: params.get("disk_size")?,
+            needed: params.get("needed")?,
+        })
+    }
+
+    fn part1(input: &Self::Input) -> Result<usize> {
+        // Total size of directories of at most 100000
+        let mut total = 0;
+        for value in input.sizes.values() {
+            if *value <= 100000 {
+                total += value;
+            }
+        }
+        Ok(total)
+    }
+
+    fn part2(input: &Self::Input) -> Result<usize> {
+        let remaining = input.disk_size.saturating_sub(input.sizes["/"]);
+        let min_dir_sizes_to_delete = input.needed.saturating_sub(remaining);
+        crate::info!(
+            "remaining space: {}. Target to delete: {}",
+            remaining,
+            min_dir_sizes_to_delete
+        );
+
+        let mut smallest = usize::MAX;
+        for value in input.sizes.values() {
+            if *value < smallest && *value > min_dir_sizes_to_delete {
+                smallest = *value;
+            }
         }
+        Ok(smallest)
     }
 
-    let remaining = 70000000 - sizes["/"];
-    let min_dir_sizes_to_delete = 30000000 - remaining;
-    println!(
-        "remaining space: {}. Target to delete: {}",
-        remaining, min_dir_sizes_to_delete
-    );
-
-    let mut smallest = usize::MAX;
-    for value in sizes.values() {
-        if *value < smallest && *value > min_dir_sizes_to_delete {
-            smallest = *value;
+    // `size` directories, filling most of the disk
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut root = Dir::random(rng, size.saturating_sub(1));
+        // Leave the update something to clear, but not everything
+        let used = rng.range(41000000..=69000000);
+        if root.size() < used {
+            root.files
+                .push(("backup.tar".to_string(), used - root.size()));
         }
+        let mut input = "$ cd /\n".to_string();
+        root.explore(&mut input);
+        Generated::new(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day07::part1(&example_input::<Day07>()).unwrap(), 95437);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day07::part2(&example_input::<Day07>()).unwrap(), 24933642);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day07>(7);
+        assert_eq!(Day07::part1(&input).unwrap(), 1581595);
+        assert_eq!(Day07::part2(&input).unwrap(), 1544176);
     }
-    println!("Size of smallest big directory: {}", smallest);
 }
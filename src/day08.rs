@@ -1,101 +1,46 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
-pub fn step1() {
-    let mut grid: Vec<Vec<i32>> = vec![];
-    for line in read_list("inputs/day08.txt") {
-        let row: Vec<i32> = line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as i32)
-            .collect();
-        grid.push(row);
-    }
-
-    let mut visible_trees: HashSet<(usize, usize)> = HashSet::new();
+use std::collections::HashSet;
 
-    // Edge trees are visible even at height 0, so ensure max_height starts -1
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    grid::{Grid, Pos},
+    params::Params,
+    solution::Solution,
+};
 
-    // Forward across lines
-    for (row_idx, row) in grid.iter().enumerate() {
-        let mut max_height = -1;
-        for (col_idx, height) in row.iter().enumerate() {
-            if height > &max_height {
-                visible_trees.insert((row_idx, col_idx));
-                max_height = *height;
-            }
-        }
-    }
-    // Backward across lines
-    for (row_idx, row) in grid.iter().enumerate() {
-        let mut max_height = -1;
-        for (col_idx, height) in row.iter().enumerate().rev() {
-            if height > &max_height {
-                visible_trees.insert((row_idx, col_idx));
-                max_height = *height;
-            }
-        }
-    }
-    // Down each column
-    for col_idx in 0..grid[0].len() {
-        let mut max_height = -1;
-        for row_idx in 0..grid.len() {
-            let height = grid[row_idx][col_idx];
-            if height > max_height {
-                visible_trees.insert((row_idx, col_idx));
-                max_height = height;
-            }
-        }
-    }
-    // Up each column
-    for col_idx in 0..grid[0].len() {
-        let mut max_height = -1;
-        for row_idx in (0..grid.len()).rev() {
-            let height = grid[row_idx][col_idx];
-            if height > max_height {
-                visible_trees.insert((row_idx, col_idx));
-                max_height = height;
-            }
-        }
-    }
-    println!("Visible trees: {}", visible_trees.len());
+fn read_heights(input: &str) -> Result<Grid<i32>> {
+    Grid::parse(input, |c| c.to_digit(10).map(|h| h as i32))
 }
 
-fn scenic_score(row_idx: usize, col_idx: usize, grid: &Vec<Vec<i32>>) -> i32 {
-    let height = grid[row_idx][col_idx];
+fn scenic_score(pos: Pos, grid: &Grid<i32>) -> i32 {
+    let (row_idx, col_idx) = pos;
+    let height = grid[pos];
 
     let mut view_dist_right = 0;
-    for check_col in (col_idx + 1)..grid[0].len() {
+    for check_height in grid.row(row_idx).skip(col_idx + 1) {
         view_dist_right += 1;
-        if grid[row_idx][check_col] >= height {
+        if *check_height >= height {
             break;
         }
     }
     let mut view_dist_left = 0;
-    for check_col in (0..col_idx).rev() {
+    for check_height in grid.row(row_idx).take(col_idx).rev() {
         view_dist_left += 1;
-        if grid[row_idx][check_col] >= height {
+        if *check_height >= height {
             break;
         }
     }
     let mut view_dist_up = 0;
-    for check_row in (0..row_idx).rev() {
+    for check_height in grid.column(col_idx).take(row_idx).rev() {
         view_dist_up += 1;
-        if grid[check_row][col_idx] >= height {
+        if *check_height >= height {
             break;
         }
     }
     let mut view_dist_down = 0;
-    for check_row in (row_idx + 1)..grid.len() {
+    for check_height in grid.column(col_idx).skip(row_idx + 1) {
         view_dist_down += 1;
-        if grid[check_row][col_idx] >= height {
+        if *check_height >= height {
             break;
         }
     }
@@ -103,25 +48,109 @@ fn scenic_score(row_idx: usize, col_idx: usize, grid: &Vec<Vec<i32>>) -> i32 {
     view_dist_down * view_dist_left * view_dist_right * view_dist_up
 }
 
-pub fn step2() {
-    let mut grid: Vec<Vec<i32>> = vec![];
-    for line in read_list("inputs/day08.txt") {
-        let row: Vec<i32> = line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as i32)
-            .collect();
-        grid.push(row);
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = Grid<i32>;
+    type Answer1 = usize;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day08.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        read_heights(input)
     }
 
-    let mut scenic_score_max = 0;
-    for (row_idx, row) in grid.iter().enumerate() {
-        for col_idx in 0..row.len() {
-            let score = scenic_score(row_idx, col_idx, &grid);
+    fn part1(input: &Self::Input) -> Result<usize> {
+        let grid = input;
+        let mut visible_trees: HashSet<Pos> = HashSet::new();
+
+        // Edge trees are visible even at height 0, so ensure max_height starts -1
+
+        for row_idx in 0..grid.height() {
+            // Forward across lines
+            let mut max_height = -1;
+            for (col_idx, height) in grid.row(row_idx).enumerate() {
+                if *height > max_height {
+                    visible_trees.insert((row_idx, col_idx));
+                    max_height = *height;
+                }
+            }
+            // Backward across lines
+            let mut max_height = -1;
+            for (col_idx, height) in grid.row(row_idx).enumerate().rev() {
+                if *height > max_height {
+                    visible_trees.insert((row_idx, col_idx));
+                    max_height = *height;
+                }
+            }
+        }
+        for col_idx in 0..grid.width() {
+            // Down each column
+            let mut max_height = -1;
+            for (row_idx, height) in grid.column(col_idx).enumerate() {
+                if *height > max_height {
+                    visible_trees.insert((row_idx, col_idx));
+                    max_height = *height;
+                }
+            }
+            // Up each column
+            let mut max_height = -1;
+            for (row_idx, height) in grid.column(col_idx).enumerate().rev() {
+                if *height > max_height {
+                    visible_trees.insert((row_idx, col_idx));
+                    max_height = *height;
+                }
+            }
+        }
+        Ok(visible_trees.len())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        let grid = input;
+        let mut scenic_score_max = 0;
+        for pos in grid.positions() {
+            let score = scenic_score(pos, grid);
             if score > scenic_score_max {
                 scenic_score_max = score;
             }
         }
+        Ok(scenic_score_max)
     }
 
-    println!("Max scenic score: {}", scenic_score_max);
+    // A `size` by `size` grid of trees
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut input = String::new();
+        for _ in 0..size.max(1) {
+            for _ in 0..size.max(1) {
+                input.push((b'0' + rng.below(10) as u8) as char);
+            }
+            input.push('\n');
+        }
+        Generated::new(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day08::part1(&example_input::<Day08>()).unwrap(), 21);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day08::part2(&example_input::<Day08>()).unwrap(), 8);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day08>(8);
+        assert_eq!(Day08::part1(&input).unwrap(), 1538);
+        assert_eq!(Day08::part2(&input).unwrap(), 496125);
+    }
 }
@@ -1,128 +1,162 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::collections::HashSet;
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    geometry::{Heading, Point},
+    input::numbered_lines,
+    params::Params,
+    solution::Solution,
+};
 
-fn adjacent(head: (i32, i32), tail: (i32, i32)) -> bool {
-    (head.0 - tail.0).abs() <= 1 && (head.1 - tail.1).abs() <= 1
+fn adjacent(head: Point, tail: Point) -> bool {
+    head.chebyshev(tail) <= 1
 }
 
-fn follow(head: (i32, i32), tail: (i32, i32), direction: &str) -> ((i32, i32), (i32, i32)) {
-    let mut head = head;
-    let mut tail = tail;
-    match direction {
-        "U" => {
-            head = (head.0 + 1, head.1);
-        }
-        "D" => {
-            head = (head.0 - 1, head.1);
-        }
-        "L" => {
-            head = (head.0, head.1 - 1);
-        }
-        "R" => {
-            head = (head.0, head.1 + 1);
-        }
-        _ => panic!("Unknown direction"),
-    };
-    tail = catch_up(head, tail);
-    (head, tail)
+fn follow(head: Point, tail: Point, direction: Heading) -> (Point, Point) {
+    let head = head.step(direction);
+    (head, catch_up(head, tail))
 }
 
-fn catch_up(head: (i32, i32), tail: (i32, i32)) -> (i32, i32) {
-    let mut tail = tail;
+fn catch_up(head: Point, tail: Point) -> Point {
     if adjacent(head, tail) {
         return tail;
     }
-    if head.0 == tail.0 {
-        tail.1 += (head.1 - tail.1).signum();
-    } else if head.1 == tail.1 {
-        tail.0 += (head.0 - tail.0).signum();
-    } else {
-        // differ in both dimensions
-        tail.0 += (head.0 - tail.0).signum();
-        tail.1 += (head.1 - tail.1).signum();
-    }
-    tail
+    // Straight along a row or column, otherwise diagonally
+    tail + (head - tail).signum()
 }
 
-pub fn step1() {
-    // row, column; 'Up' is +ve.
-    let mut head = (0, 0);
-    let mut tail = (0, 0);
-    let mut visited: HashSet<(i32, i32)> = HashSet::new();
-
-    visited.insert(tail);
-    for rule in read_list("inputs/day09.txt") {
-        let mut words = rule.split_whitespace();
-        let direction = words.next().unwrap();
-        let count = words.next().unwrap().parse::<i32>().unwrap();
-        for _ in 0..count {
-            (head, tail) = follow(head, tail, direction);
-            visited.insert(tail);
+// The area around the start, with the knots as '*' and the start as 's'.
+fn render(head: Point, rope: &[Point]) -> String {
+    let mut out = String::new();
+    for y in -9..=10 {
+        for x in -10..20 {
+            let point = Point::new(x, y);
+            if point == head || rope.contains(&point) {
+                out.push('*');
+            } else if point == Point::ORIGIN {
+                out.push('s');
+            } else {
+                out.push('.');
+            }
         }
+        out.push('\n');
     }
+    out
+}
 
-    println!("Visited cells: {}", visited.len());
+fn read_rules(input: &str) -> Result<Vec<(Heading, i32)>> {
+    let mut rules = vec![];
+    for rule in numbered_lines(input)? {
+        let mut words = rule.text.split_whitespace();
+        let field = rule.field(words.next(), "direction")?;
+        let direction = match field {
+            "U" => Heading::North,
+            "D" => Heading::South,
+            "L" => Heading::West,
+            "R" => Heading::East,
+            _ => return Err(rule.field_error(field, "direction must be U, D, L or R")),
+        };
+        let count = rule.parse::<i32>(rule.field(words.next(), "step count")?)?;
+        rules.push((direction, count));
+    }
+    Ok(rules)
 }
 
-pub fn step2() {
-    // row, column; 'Up' is +ve.
-    const ROPE_LEN: usize = 9;
-    let mut head = (0, 0);
-    let mut rope: Vec<(i32, i32)> = vec![]; // the 9 knots following the head.
-    for _ in 0..ROPE_LEN {
-        rope.push((0, 0));
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Vec<(Heading, i32)>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day09.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        read_rules(input)
     }
 
-    let mut visited: HashSet<(i32, i32)> = HashSet::new();
-
-    visited.insert(rope[ROPE_LEN - 1]);
-    for rule in read_list("inputs/day09.txt") {
-        let mut words = rule.split_whitespace();
-        let direction = words.next().unwrap();
-        let count = words.next().unwrap().parse::<i32>().unwrap();
-        println!("{} {}", direction, count);
-        for _ in 0..count {
-            (head, rope[0]) = follow(head, rope[0], direction);
-            println!("Head {:?}", head);
-            for knot in 1..ROPE_LEN {
-                println!(
-                    "knot {}, rope[{}] = {:?}, rope[{}] = {:?}",
-                    knot,
-                    knot,
-                    rope[knot],
-                    knot - 1,
-                    rope[knot - 1]
-                );
-                if adjacent(rope[knot], rope[knot - 1]) {
-                    break;
-                }
-                rope[knot] = catch_up(rope[knot - 1], rope[knot]);
+    fn part1(input: &Self::Input) -> Result<usize> {
+        let mut head = Point::ORIGIN;
+        let mut tail = Point::ORIGIN;
+        let mut visited: HashSet<Point> = HashSet::new();
+
+        visited.insert(tail);
+        for (direction, count) in input {
+            for _ in 0..*count {
+                (head, tail) = follow(head, tail, *direction);
+                visited.insert(tail);
             }
-            println!("{:?}", rope);
-            visited.insert(rope[ROPE_LEN - 1]);
         }
+        Ok(visited.len())
+    }
+
+    fn part2(input: &Self::Input) -> Result<usize> {
+        const ROPE_LEN: usize = 9;
+        let mut head = Point::ORIGIN;
+        let mut rope = [Point::ORIGIN; ROPE_LEN]; // the 9 knots following the head.
+
+        let mut visited: HashSet<Point> = HashSet::new();
 
-        for x in -10..10 {
-            for y in -10..20 {
-                if (9 - x, y) == head || rope.contains(&(9 - x, y)) {
-                    print!("*");
-                } else if (9 - x, y) == (0, 0) {
-                    print!("s");
-                } else {
-                    print!(".");
+        visited.insert(rope[ROPE_LEN - 1]);
+        for (direction, count) in input {
+            crate::trace!("{:?} {}", direction, count);
+            for _ in 0..*count {
+                (head, rope[0]) = follow(head, rope[0], *direction);
+                crate::trace!("Head {}", head);
+                for knot in 1..ROPE_LEN {
+                    crate::trace!(
+                        "knot {}, rope[{}] = {}, rope[{}] = {}",
+                        knot,
+                        knot,
+                        rope[knot],
+                        knot - 1,
+                        rope[knot - 1]
+                    );
+                    if adjacent(rope[knot], rope[knot - 1]) {
+                        break;
+                    }
+                    rope[knot] = catch_up(rope[knot - 1], rope[knot]);
                 }
+                crate::trace!("{:?}", rope);
+                visited.insert(rope[ROPE_LEN - 1]);
             }
-            println!();
+            crate::trace!("{}", render(head, &rope));
+        }
+        Ok(visited.len())
+    }
+
+    // `size` moves of the head
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut input = String::new();
+        for _ in 0..size.max(1) {
+            let direction = rng.choose(&["U", "D", "L", "R"]);
+            input += &format!("{} {}\n", direction, rng.range(1..=20));
         }
+        Generated::new(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day09::part1(&example_input::<Day09>()).unwrap(), 13);
     }
 
-    println!("Full rope tail visited cells: {}", visited.len());
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day09::part2(&example_input::<Day09>()).unwrap(), 1);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day09>(9);
+        assert_eq!(Day09::part1(&input).unwrap(), 6266);
+        assert_eq!(Day09::part2(&input).unwrap(), 2369);
+    }
 }
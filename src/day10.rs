@@ -1,103 +1,161 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::Params,
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
+// None for noop, Some(value) for addx.
+fn read_instructions(input: &str) -> Result<Vec<Option<i32>>> {
+    let mut instructions = vec![];
+    for instruction in numbered_lines(input)? {
+        let words: Vec<&str> = instruction.text.split_whitespace().collect();
+        match words[..] {
+            ["noop"] => instructions.push(None),
+            ["addx", value] => instructions.push(Some(instruction.parse(value)?)),
+            _ => return Err(instruction.error("expected 'noop' or 'addx <value>'")),
+        }
+    }
+    Ok(instructions)
 }
 
-pub fn step1() {
-    let mut cycle = 0;
-    let mut reg_x = 1;
-    let mut signal_strength = 0;
-    for instruction in read_list("inputs/day10.txt") {
-        if instruction.starts_with("noop") {
-            cycle += 1;
-            if cycle % 40 == 20 {
-                signal_strength += reg_x * cycle;
-                println!("cycle {}, {} strength {}", cycle, reg_x, signal_strength);
-            }
-        } else {
-            cycle += 1;
-            if cycle % 40 == 20 {
-                signal_strength += reg_x * cycle;
-                println!("cycle {}, {} strength {}", cycle, reg_x, signal_strength);
-            }
-            cycle += 1;
-            if cycle % 40 == 20 {
-                signal_strength += reg_x * cycle;
-                println!("cycle {}, {} strength {}", cycle, reg_x, signal_strength);
-            }
-            reg_x += instruction
-                .split_whitespace()
-                .nth(1)
-                .unwrap()
-                .parse::<i32>()
-                .unwrap();
-        }
+fn draw(display: &mut String, cycle: i32, reg_x: i32) {
+    if cycle % 40 == 0 && cycle != 0 {
+        display.push('\n');
     }
-    println!("Signal strength: {}", signal_strength);
+    display.push(if (reg_x - cycle % 40).abs() <= 1 {
+        '#'
+    } else {
+        ' '
+    });
 }
 
-pub fn step2() {
-    let mut display: Vec<char> = vec![];
-    let mut cycle = -1;
-    let mut reg_x = 1;
-    for instruction in read_list("inputs/day10.txt") {
-        if instruction.starts_with("noop") {
-            cycle += 1;
-            if cycle % 40 == 0 {
-                for c in &display {
-                    print!("{}", c);
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Vec<Option<i32>>;
+    type Answer1 = i32;
+    type Answer2 = String;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day10.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        read_instructions(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        let mut cycle = 0;
+        let mut reg_x = 1;
+        let mut signal_strength = 0;
+        for instruction in input {
+            if let Some(value) = instruction {
+                cycle += 1;
+                if cycle % 40 == 20 {
+                    signal_strength += reg_x * cycle;
+                    crate::info!("cycle {}, {} strength {}", cycle, reg_x, signal_strength);
                 }
-                display.clear();
-                println!();
-            }
-            display.push(if ((reg_x - cycle % 40) as i32).abs() <= 1 {
-                '#'
+                cycle += 1;
+                if cycle % 40 == 20 {
+                    signal_strength += reg_x * cycle;
+                    crate::info!("cycle {}, {} strength {}", cycle, reg_x, signal_strength);
+                }
+                reg_x += value;
             } else {
-                ' '
-            });
-        } else {
-            cycle += 1;
-            if cycle % 40 == 0 {
-                for c in &display {
-                    print!("{}", c);
+                cycle += 1;
+                if cycle % 40 == 20 {
+                    signal_strength += reg_x * cycle;
+                    crate::info!("cycle {}, {} strength {}", cycle, reg_x, signal_strength);
                 }
-                display.clear();
-                println!();
             }
-            display.push(if ((reg_x - cycle % 40) as i32).abs() <= 1 {
-                '#'
+        }
+        Ok(signal_strength)
+    }
+
+    fn part2(input: &Self::Input) -> Result<String> {
+        // The CRT image, one row of 40 pixels per line
+        let mut display = String::new();
+        let mut cycle: i32 = -1;
+        let mut reg_x = 1;
+        for instruction in input {
+            if let Some(value) = instruction {
+                cycle += 1;
+                draw(&mut display, cycle, reg_x);
+                cycle += 1;
+                draw(&mut display, cycle, reg_x);
+                reg_x += value;
             } else {
-                ' '
-            });
-            cycle += 1;
-            if cycle % 40 == 0 {
-                for c in &display {
-                    print!("{}", c);
-                }
-                display.clear();
-                println!();
+                cycle += 1;
+                draw(&mut display, cycle, reg_x);
             }
-            display.push(if ((reg_x - cycle % 40) as i32).abs() <= 1 {
-                '#'
+        }
+        Ok(display)
+    }
+
+    // At least `size` instructions, and enough to draw the whole screen
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut input = String::new();
+        let (mut count, mut cycles, mut reg_x) = (0, 0, 1);
+        while count < size || cycles < 240 {
+            if rng.chance(30) {
+                input += "noop\n";
+                cycles += 1;
             } else {
-                ' '
-            });
-            reg_x += instruction
-                .split_whitespace()
-                .nth(1)
-                .unwrap()
-                .parse::<i32>()
-                .unwrap();
+                // Keep the sprite roughly on the screen
+                let mut value = rng.range(-10..=10);
+                if !(-5..=45).contains(&(reg_x + value)) {
+                    value = -value;
+                }
+                reg_x += value;
+                input += &format!("addx {}\n", value);
+                cycles += 2;
+            }
+            count += 1;
         }
+        Generated::new(input)
     }
-    for c in &display {
-        print!("{}", c);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day10::part1(&example_input::<Day10>()).unwrap(), 13140);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(
+            Day10::part2(&example_input::<Day10>()).unwrap(),
+            concat!(
+                "##  ##  ##  ##  ##  ##  ##  ##  ##  ##  \n",
+                "###   ###   ###   ###   ###   ###   ### \n",
+                "####    ####    ####    ####    ####    \n",
+                "#####     #####     #####     #####     \n",
+                "######      ######      ######      ####\n",
+                "#######       #######       #######     "
+            )
+        );
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day10>(10);
+        assert_eq!(Day10::part1(&input).unwrap(), 13520);
+        assert_eq!(
+            Day10::part2(&input).unwrap(),
+            concat!(
+                "###   ##  ###  #  # ###  ####  ##  ###  \n",
+                "#  # #  # #  # #  # #  # #    #  # #  # \n",
+                "#  # #    #  # #### ###  ###  #  # ###  \n",
+                "###  # ## ###  #  # #  # #    #### #  # \n",
+                "#    #  # #    #  # #  # #    #  # #  # \n",
+                "#     ### #    #  # ###  #### #  # ###  "
+            )
+        );
     }
-    display.clear();
-    println!();
 }
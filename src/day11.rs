@@ -1,4 +1,13 @@
-struct Monkey {
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::{numbered_paragraphs, Line},
+    params::Params,
+    solution::Solution,
+};
+
+#[derive(Debug, Clone)]
+pub struct Monkey {
     pub items: Vec<i64>,
     op: char,
     immediate: i64,
@@ -28,16 +37,50 @@ impl Monkey {
         }
     }
 
-    pub fn turn(&mut self, divide: bool) -> Vec<(i64, usize)> {
-        let base = if divide {
-            // For consistency, to avoid not doing the mod operation
-            i64::MAX
-        } else {
-            // The monkey test_divisors are the following - all primes, so a
-            // common modulo base of the product of these will be equal
-            3 * 13 * 2 * 11 * 5 * 17 * 19 * 7
+    fn parse(lines: &[Line]) -> Result<Self> {
+        if lines.len() != 6 {
+            return Err(lines[0].error(format!(
+                "expected 6 lines describing a monkey, found {}",
+                lines.len()
+            )));
+        }
+        // Each line is "<label>: <value>"; the label is only checked for
+        // the operation, as it's the one line with varying structure.
+        let value = |idx: usize| {
+            let line = &lines[idx];
+            line.field(line.text.split(": ").nth(1), "': <value>'")
+        };
+        let last_word = |idx: usize| -> Result<usize> {
+            let line = &lines[idx];
+            line.parse(line.field(value(idx)?.split_whitespace().last(), "value")?)
         };
 
+        let items: Vec<i64> = value(1)?
+            .split(", ")
+            .map(|i| lines[1].parse(i))
+            .collect::<Result<_>>()?;
+        let (op, immediate) = match value(2)?.split_whitespace().collect::<Vec<_>>()[..] {
+            ["new", "=", "old", "*", "old"] => ('s', 0),
+            ["new", "=", "old", op @ ("*" | "+"), immediate] => {
+                (op.chars().next().unwrap(), lines[2].parse(immediate)?)
+            }
+            _ => return Err(lines[2].error("unknown operation")),
+        };
+        let test_divisor = last_word(3)?;
+        if test_divisor == 0 {
+            return Err(lines[3].error("test divisor must be non-zero"));
+        }
+        Ok(Self::new(
+            &items,
+            op,
+            immediate,
+            test_divisor as i64,
+            last_word(4)?,
+            last_word(5)?,
+        ))
+    }
+
+    pub fn turn(&mut self, divide: bool, base: i64) -> Vec<(i64, usize)> {
         let mut result = vec![];
         for item in &self.items {
             // inspect item
@@ -67,52 +110,135 @@ impl Monkey {
     }
 }
 
-fn generate_data() -> Vec<Monkey> {
-    // I couldn't be bothered to write a parser, though I probably should have as
-    // I made a mistake transcribing the data...
-    let m0 = Monkey::new(&[64, 89, 65, 95], '*', 7, 3, 4, 1);
-    let m1 = Monkey::new(&[76, 66, 74, 87, 70, 56, 51, 66], '+', 5, 13, 7, 3);
-    let m2 = Monkey::new(&[91, 60, 63], 's', 0, 2, 6, 5);
-    let m3 = Monkey::new(&[92, 61, 79, 97, 79], '+', 6, 11, 2, 6);
-    let m4 = Monkey::new(&[93, 54], '*', 11, 5, 1, 7);
-    let m5 = Monkey::new(&[60, 79, 92, 69, 88, 82, 70], '+', 8, 17, 4, 0);
-    let m6 = Monkey::new(&[64, 57, 73, 89, 55, 53], '+', 1, 19, 0, 5);
-    let m7 = Monkey::new(&[62], '+', 4, 7, 3, 2);
-
-    vec![m0, m1, m2, m3, m4, m5, m6, m7]
+fn read_monkeys(input: &str) -> Result<Vec<Monkey>> {
+    let paragraphs = numbered_paragraphs(input)?;
+    let monkeys: Vec<Monkey> = paragraphs
+        .iter()
+        .map(|lines| Monkey::parse(lines))
+        .collect::<Result<_>>()?;
+    for (monkey, lines) in monkeys.iter().zip(&paragraphs) {
+        for (target, line) in [
+            (monkey.target_true, &lines[4]),
+            (monkey.target_false, &lines[5]),
+        ] {
+            if target >= monkeys.len() {
+                return Err(line.error(format!("no monkey {}", target)));
+            }
+        }
+    }
+    if monkeys.len() < 2 {
+        return Err(Error::new("at least two monkeys are needed"));
+    }
+    Ok(monkeys)
 }
 
-pub fn step1() {
-    let mut monkeys = generate_data();
+/// Product of the two highest inspection counts after `rounds` rounds.
+pub fn monkey_business(monkeys: &[Monkey], rounds: usize, divide: bool) -> i64 {
+    let mut monkeys = monkeys.to_vec();
+    let base = if divide {
+        // For consistency, to avoid not doing the mod operation
+        i64::MAX
+    } else {
+        // The monkey test_divisors are all primes, so a common modulo
+        // base of the product of these will be equal
+        monkeys.iter().map(|m| m.test_divisor).product()
+    };
 
-    for _ in 0..20 {
+    for _ in 0..rounds {
         // RUST: is there a nicer way of modifying multiple entries of
         // a container than just indexing (rather than iterating over)
         // the container?
         for m in 0..monkeys.len() {
-            let thrown = monkeys[m].turn(true);
+            let thrown = monkeys[m].turn(divide, base);
             for (v, t) in thrown {
                 monkeys[t].items.push(v);
             }
         }
     }
 
-    monkeys.sort_by(|a, b| b.inspected.cmp(&a.inspected));
-    println!("{}", monkeys[0].inspected * monkeys[1].inspected);
+    monkeys.sort_by_key(|m| std::cmp::Reverse(m.inspected));
+    monkeys[0].inspected * monkeys[1].inspected
 }
 
-pub fn step2() {
-    let mut monkeys = generate_data();
+pub struct Day11;
 
-    for _ in 0..10000 {
-        for m in 0..monkeys.len() {
-            let thrown = monkeys[m].turn(false);
-            for (v, t) in thrown {
-                monkeys[t].items.push(v);
-            }
+impl Solution for Day11 {
+    type Input = Vec<Monkey>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day11.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        read_monkeys(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i64> {
+        Ok(monkey_business(input, 20, true))
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        Ok(monkey_business(input, 10000, false))
+    }
+
+    // `size` monkeys, three to nine as each tests a different prime
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let count = size.clamp(3, 9);
+        let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+        rng.shuffle(&mut primes);
+        // Part 1 doesn't keep worry levels in check, so they mustn't grow:
+        // only one monkey squares them, and nobody throws it anything, and
+        // the others multiply by no more than the relief divides by
+        let squarer = rng.below(count);
+        let mut monkeys = vec![];
+        for (idx, prime) in primes.iter().enumerate().take(count) {
+            let items: Vec<String> = (0..rng.range(1..=6))
+                .map(|_| rng.range(50..=99).to_string())
+                .collect();
+            let operation = if idx == squarer {
+                "old * old".to_string()
+            } else if rng.chance(50) {
+                format!("old * {}", rng.range(2..=3))
+            } else {
+                format!("old + {}", rng.range(1..=8))
+            };
+            let mut targets: Vec<usize> =
+                (0..count).filter(|&m| m != idx && m != squarer).collect();
+            rng.shuffle(&mut targets);
+            monkeys.push(format!(
+                "Monkey {}:\n  Starting items: {}\n  Operation: new = {}\n  Test: divisible by {}\n    If true: throw to monkey {}\n    If false: throw to monkey {}\n",
+                idx,
+                items.join(", "),
+                operation,
+                prime,
+                targets[0],
+                targets[targets.len() - 1]
+            ));
         }
+        Generated::new(monkeys.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day11::part1(&example_input::<Day11>()).unwrap(), 10605);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day11::part2(&example_input::<Day11>()).unwrap(), 2713310158);
     }
 
-    monkeys.sort_by(|a, b| b.inspected.cmp(&a.inspected));
-    println!("{}", monkeys[0].inspected * monkeys[1].inspected);
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day11>(11);
+        assert_eq!(Day11::part1(&input).unwrap(), 113232);
+        assert_eq!(Day11::part2(&input).unwrap(), 29703395016);
+    }
 }
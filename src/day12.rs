@@ -1,200 +1,164 @@
-use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashMap},
-    fs::File,
-    io::{BufRead, BufReader},
+use std::collections::HashSet;
+
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    grid::{Grid, Pos},
+    params::Params,
+    search::{astar, bfs, Graph},
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
-struct HeightMap {
-    row_count: usize,
-    col_count: usize,
-    start_point: (usize, usize),
-    end_point: (usize, usize),
-    elevation: HashMap<(usize, usize), i32>,
+pub struct HeightMap {
+    start_point: Pos,
+    end_point: Pos,
+    elevation: Grid<i32>,
 }
 
 impl HeightMap {
-    fn new(filename: &str) -> Self {
-        let mut grid: HashMap<(usize, usize), i32> = HashMap::new();
-        let mut col_count = 0;
-        for (row_idx, line) in read_list(filename).iter().enumerate() {
-            // Read as base 36 and subtract 9 to get a = 1, z = 26
-            for (col_idx, height) in line
-                .chars()
-                .map(|c| c.to_digit(36).unwrap() as i32 - 9)
-                .enumerate()
-            {
-                grid.insert((row_idx, col_idx), height);
-            }
-            col_count = line.len();
-        }
-        let mut start_point = (0, 0);
-        let mut end_point = (0, 0);
-        for (row, line) in read_list("inputs/day12.txt").iter().enumerate() {
-            if let Some(col) = line.find('S') {
-                start_point = (row, col);
-                grid.insert((row, col), 1); // start point 'has elevation a'
-            }
-            if let Some(col) = line.find('E') {
-                end_point = (row, col);
-                grid.insert((row, col), 26); // '(E) has elevation z'
+    fn new(input: &str) -> Result<Self> {
+        let chars = Grid::parse(input, |c| matches!(c, 'a'..='z' | 'S' | 'E').then_some(c))?;
+        let mut start_point = None;
+        let mut end_point = None;
+        for (pos, &c) in chars.iter() {
+            let point = match c {
+                'S' => &mut start_point,
+                'E' => &mut end_point,
+                _ => continue,
+            };
+            if point.is_some() {
+                return Err(chars.error_at(pos, &format!("more than one '{}'", c)));
             }
+            *point = Some(pos);
         }
 
-        Self {
-            row_count: grid.len() / col_count,
-            col_count,
-            start_point,
-            end_point,
-            elevation: grid,
-        }
+        let elevation = chars.map(|&c| match c {
+            'S' => 1,  // start point 'has elevation a'
+            'E' => 26, // '(E) has elevation z'
+            // Read as base 36 and subtract 9 to get a = 1, z = 26
+            _ => c.to_digit(36).unwrap() as i32 - 9,
+        });
+        Ok(Self {
+            start_point: start_point.ok_or_else(|| Error::new("no start point 'S'"))?,
+            end_point: end_point.ok_or_else(|| Error::new("no end point 'E'"))?,
+            elevation,
+        })
     }
+}
 
-    fn neighbours(&self, coord: (usize, usize)) -> Vec<(usize, usize)> {
-        let current_elevation = self.elevation[&coord];
-        let mut result = vec![];
-        if coord.0 > 0 {
-            let neighbour = (coord.0 - 1, coord.1);
-            if self.elevation[&neighbour] - current_elevation <= 1 {
-                result.push(neighbour);
-            }
-        }
-        if coord.0 < self.row_count - 1 {
-            let neighbour = (coord.0 + 1, coord.1);
-            if self.elevation[&neighbour] - current_elevation <= 1 {
-                result.push(neighbour);
-            }
-        }
-        if coord.1 > 0 {
-            let neighbour = (coord.0, coord.1 - 1);
-            if self.elevation[&neighbour] - current_elevation <= 1 {
-                result.push(neighbour);
-            }
-        }
-        if coord.1 < self.col_count - 1 {
-            let neighbour = (coord.0, coord.1 + 1);
-            if self.elevation[&neighbour] - current_elevation <= 1 {
-                result.push(neighbour);
-            }
-        }
-        result
+impl Graph for HeightMap {
+    type Node = Pos;
+
+    // Steps can climb at most one unit, but drop any distance
+    fn neighbours(&self, &coord: &Pos) -> Vec<Pos> {
+        let current_elevation = self.elevation[coord];
+        self.elevation
+            .neighbours4(coord)
+            .filter(|&n| self.elevation[n] - current_elevation <= 1)
+            .collect()
     }
 }
 
-// The `State` struct & `shortest_path` functions are derived from
-// https://doc.rust-lang.org/std/collections/binary_heap/index.html
-//
-// Only minor changes have been required / made to support the
-// heightmap grid as a graph source, using the `neighbours()` method
-// to enumerate edges, each of which has cost 1.
-
-// START CODE DERIVED FROM https://doc.rust-lang.org/std/collections/binary_heap/index.html
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: (usize, usize),
-}
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = HeightMap;
+    type Answer1 = usize;
+    type Answer2 = usize;
 
-// The priority queue depends on `Ord`.
-// Explicitly implement the trait so the queue becomes a min-heap
-// instead of a max-heap.
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Notice that the we flip the ordering on costs.
-        // In case of a tie we compare positions - this step is necessary
-        // to make implementations of `PartialEq` and `Ord` consistent.
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day12.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        HeightMap::new(input)
     }
-}
 
-// `PartialOrd` needs to be implemented as well.
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    fn part1(input: &Self::Input) -> Result<usize> {
+        let end = input.end_point;
+        // Every step moves one square, so the grid distance never overestimates
+        let distance_to_end = |&(row, col): &Pos| row.abs_diff(end.0) + col.abs_diff(end.1);
+        astar(input, [input.start_point], |&p| p == end, distance_to_end)
+            .target_distance()
+            .ok_or_else(|| Error::new("no route to the end point"))
     }
-}
 
-// Dijkstra's shortest path algorithm.
-fn shortest_path(heightmap: &HeightMap, start_point: (usize, usize)) -> usize {
-    let mut heap = BinaryHeap::new();
-    let mut dist: HashMap<(usize, usize), usize> = HashMap::new();
-
-    // We're at `start`, with a zero cost
-    dist.insert(start_point, 0);
-    heap.push(State {
-        cost: 0,
-        position: start_point,
-    });
-
-    let mut distance = usize::MAX;
-    // Examine the frontier with lower cost nodes first (min-heap)
-    while let Some(State { cost, position }) = heap.pop() {
-        // Alternatively we could have continued to find all shortest paths
-        if position == heightmap.end_point {
-            distance = cost;
-            break;
-        }
-        // Important as we may have already found a better way
-        if let Some(candidate_dist) = dist.get(&position) {
-            if cost > *candidate_dist {
-                continue;
+    fn part2(input: &Self::Input) -> Result<usize> {
+        // Search from every square at elevation 'a' at once; the start
+        // point already has that elevation
+        let candidates = input
+            .elevation
+            .iter()
+            .filter(|(_, &height)| height == 1)
+            .map(|(pos, _)| pos);
+        bfs(input, candidates, |&p| p == input.end_point)
+            .target_distance()
+            .ok_or_else(|| Error::new("no route to the end point"))
+    }
+
+    // A `size` by `size` hill, at least 14 across so it can rise from 'a'
+    // to 'z' gently enough to climb anywhere
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let n = size.max(14);
+        let slope = |row: usize, col: usize| (row + col) * 25 / (2 * n - 2);
+        // A route from the start to the end which is left alone when
+        // digging hollows into the slope
+        let mut route = HashSet::from([(0, 0)]);
+        let (mut row, mut col) = (0, 0);
+        while (row, col) != (n - 1, n - 1) {
+            if col == n - 1 || (row < n - 1 && rng.chance(50)) {
+                row += 1;
+            } else {
+                col += 1;
             }
+            route.insert((row, col));
         }
-        // For each node we can reach, see if we can find a way with
-        // a lower cost going through this node
-        for edge in heightmap.neighbours(position) {
-            // All 'edges' here have cost 1.
-            let next = State {
-                cost: cost + 1,
-                position: edge,
-            };
-            // If so, add it to the frontier and continue
-            if next.cost < *dist.entry(next.position).or_insert(usize::MAX) {
-                heap.push(next);
-                // Relaxation, we have now found a better way
-                dist.insert(next.position, next.cost);
+        let mut input = String::new();
+        for row in 0..n {
+            for col in 0..n {
+                let height = match (row, col) {
+                    (0, 0) => 'S',
+                    _ if (row, col) == (n - 1, n - 1) => 'E',
+                    _ if !route.contains(&(row, col)) && rng.chance(30) => {
+                        (b'a' + rng.below(slope(row, col) + 1) as u8) as char
+                    }
+                    _ => (b'a' + slope(row, col) as u8) as char,
+                };
+                input.push(height);
             }
+            input.push('\n');
         }
+        Generated::new(input)
     }
-    distance
-}
-// END CODE DERIVED FROM https://doc.rust-lang.org/std/collections/binary_heap/index.html
-
-pub fn step1() {
-    let heightmap = HeightMap::new("inputs/day12.txt");
-    let cost = shortest_path(&heightmap, heightmap.start_point);
-    println!("Distance to target: {}", cost);
 }
 
-pub fn step2() {
-    let mut heightmap = HeightMap::new("inputs/day12.txt");
-
-    // Set start point back to value for 'a'
-    heightmap.elevation.insert(heightmap.start_point, 1);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
 
-    let candidates: Vec<_> = heightmap
-        .elevation
-        .iter()
-        .filter(|coord| heightmap.elevation[coord.0] == 1)
-        .map(|p| *p.0)
-        .collect();
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day12::part1(&example_input::<Day12>()).unwrap(), 31);
+    }
 
-    let mut min_route = usize::MAX;
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day12::part2(&example_input::<Day12>()).unwrap(), 29);
+    }
 
-    for start_point in candidates {
-        let cost = shortest_path(&heightmap, start_point);
-        if cost < min_route {
-            min_route = cost;
-        }
+    #[test]
+    fn no_route() {
+        // Too steep a climb from either start
+        let map = Day12::parse("SaaE\nzzzz\n", &Params::new(Day12::PARAMS, false));
+        let map = map.unwrap();
+        assert!(Day12::part1(&map).is_err());
+        assert!(Day12::part2(&map).is_err());
     }
 
-    println!("Minimum scenic route length: {}", min_route);
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day12>(12);
+        assert_eq!(Day12::part1(&input).unwrap(), 534);
+        assert_eq!(Day12::part2(&input).unwrap(), 525);
+    }
 }
@@ -1,17 +1,16 @@
-use std::{
-    cmp::Ordering,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::cmp::Ordering;
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+use crate::{
+    error,
+    generate::{Generated, Rng},
+    input::{numbered_paragraphs, Line},
+    params::Params,
+    solution::Solution,
+};
 
 fn lex(a: &str) -> Vec<String> {
     // Return vector of elements of the list
-    //println!("Lex query: {:?}", a);
+    crate::trace!("Lex query: {:?}", a);
     let mut result = vec![];
     let mut pending = vec![];
     let mut depth = 0;
@@ -32,12 +31,12 @@ fn lex(a: &str) -> Vec<String> {
             depth -= 1;
         }
     }
-    //println!("Lex result: {:?}", result);
+    crate::trace!("Lex result: {:?}", result);
     result
 }
 
 fn ordered(a: &str, b: &str) -> Ordering {
-    //println!("Comparing {:?} {:?}", a, b);
+    crate::trace!("Comparing {:?} {:?}", a, b);
     let a_int_opt: Result<i32, _> = a.parse();
     let b_int_opt: Result<i32, _> = b.parse();
 
@@ -84,56 +83,129 @@ fn ordered(a: &str, b: &str) -> Ordering {
     }
 }
 
-pub fn step1() {
-    let mut pairs: Vec<(String, String)> = vec![];
-    let mut first: Option<String> = None;
-    let mut second: Option<String> = None;
-    for line in read_list("inputs/day13.txt") {
-        if line.is_empty() {
-            pairs.push((first.unwrap(), second.unwrap()));
-            first = None;
-            second = None;
-        } else if first.is_some() {
-            second = Some(line);
-        } else {
-            assert!(first.is_none());
-            first = Some(line);
+// A packet is a bracketed, comma-separated list of integers and lists.
+fn check_packet(line: &Line) -> error::Result<()> {
+    if !line.text.starts_with('[') {
+        return Err(line.char_error(0, "packet must start with '['"));
+    }
+    let mut depth = 0;
+    for (idx, c) in line.text.chars().enumerate() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' | '0'..='9' => (),
+            _ => return Err(line.char_error(idx, "unexpected character")),
         }
+        if depth == 0 && idx != line.text.len() - 1 {
+            return Err(line.char_error(idx, "unbalanced ']'"));
+        }
+    }
+    if depth != 0 {
+        return Err(line.error("missing ']'"));
     }
+    Ok(())
+}
 
-    let mut total = 0;
-    for (pair_idx, (a, b)) in pairs.iter().enumerate() {
-        if ordered(a, b) == Ordering::Less {
-            //println!("Right order");
-            total += pair_idx + 1; // zero-based -> one-based.
+// A list of up to four items, each an integer or (not too deeply) a list.
+fn random_packet(rng: &mut Rng, depth: usize) -> String {
+    let mut items = vec![];
+    for _ in 0..rng.below(5) {
+        if depth < 3 && rng.chance(30) {
+            items.push(random_packet(rng, depth + 1));
+        } else {
+            items.push(rng.range(0..=10).to_string());
         }
-        //println!();
     }
-    println!("Sum of ordered indices: {}", total);
+    format!("[{}]", items.join(","))
 }
 
-pub fn step2() {
-    let mut packets = vec![];
-    for line in read_list("inputs/day13.txt") {
-        if !line.is_empty() {
-            packets.push(line);
+pub struct Day13;
+
+impl Solution for Day13 {
+    // Pairs of packets
+    type Input = Vec<Vec<String>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day13.txt");
+
+    fn parse(input: &str, _params: &Params) -> error::Result<Self::Input> {
+        let mut pairs = vec![];
+        for pair in numbered_paragraphs(input)? {
+            if pair.len() != 2 {
+                return Err(pair[0].error("expected a pair of packets"));
+            }
+            for line in &pair {
+                check_packet(line)?;
+            }
+            pairs.push(pair.iter().map(|l| l.text.to_string()).collect());
+        }
+        Ok(pairs)
+    }
+
+    fn part1(input: &Self::Input) -> error::Result<usize> {
+        let mut total = 0;
+        for (pair_idx, pair) in input.iter().enumerate() {
+            if ordered(&pair[0], &pair[1]) == Ordering::Less {
+                crate::trace!("Pair {} is in the right order", pair_idx + 1);
+                total += pair_idx + 1; // zero-based -> one-based.
+            }
         }
+        Ok(total)
     }
 
-    // Add in the two divider packets
-    packets.push("[[2]]".to_string());
-    packets.push("[[6]]".to_string());
+    fn part2(input: &Self::Input) -> error::Result<usize> {
+        let mut packets = input.concat();
+
+        // Add in the two divider packets
+        packets.push("[[2]]".to_string());
+        packets.push("[[6]]".to_string());
 
-    packets.sort_by(|a, b| ordered(a, b));
+        packets.sort_by(|a, b| ordered(a, b));
+
+        let mut first_div_idx = 0;
+        let mut second_div_idx = 0;
+        for (idx, p) in packets.iter().enumerate() {
+            if p == "[[2]]" {
+                first_div_idx = idx + 1;
+            } else if p == "[[6]]" {
+                second_div_idx = idx + 1;
+            }
+        }
+        Ok(first_div_idx * second_div_idx)
+    }
 
-    let mut first_div_idx = 0;
-    let mut second_div_idx = 0;
-    for (idx, p) in packets.iter().enumerate() {
-        if p == "[[2]]" {
-            first_div_idx = idx + 1;
-        } else if p == "[[6]]" {
-            second_div_idx = idx + 1;
+    // `size` pairs of packets
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut pairs = vec![];
+        for _ in 0..size.max(1) {
+            let left = random_packet(rng, 0);
+            pairs.push(format!("{}\n{}\n", left, random_packet(rng, 0)));
         }
+        Generated::new(pairs.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day13::part1(&example_input::<Day13>()).unwrap(), 13);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day13::part2(&example_input::<Day13>()).unwrap(), 140);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day13>(13);
+        assert_eq!(Day13::part1(&input).unwrap(), 5003);
+        assert_eq!(Day13::part2(&input).unwrap(), 20280);
     }
-    println!("Decoder key: {}", first_div_idx * second_div_idx);
 }
@@ -1,145 +1,251 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    geometry::Point,
+    grid::{Grid, Pos},
+    input::{numbered_lines, Line},
+    params::{Param, Params},
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
+// "498,4", which is a field of the given line.
+fn parse_point(line: &Line, c: &str) -> Result<Point> {
+    let (x, y) = c
+        .split_once(',')
+        .ok_or_else(|| line.field_error(c, "expected a coordinate such as '498,4'"))?;
+    Ok(Point::new(line.parse(x)?, line.parse(y)?))
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-struct Coord(i32, i32);
-
-impl From<&str> for Coord {
-    fn from(c: &str) -> Self {
-        let mut parts = c.split(',');
-        Self(
-            parts.next().unwrap().parse().unwrap(),
-            parts.next().unwrap().parse().unwrap(),
-        )
-    }
-}
-
-impl From<(i32, i32)> for Coord {
-    fn from(c: (i32, i32)) -> Self {
-        Self(c.0, c.1)
+// Every point on a horizontal or vertical line, including both ends.
+fn range(from_v: Point, to_v: Point) -> Vec<Point> {
+    assert!(
+        from_v.x == to_v.x || from_v.y == to_v.y,
+        "Coords must match in one axis"
+    );
+    let step = (to_v - from_v).signum();
+    let mut result = vec![from_v];
+    let mut point = from_v;
+    while point != to_v {
+        point += step;
+        result.push(point);
     }
+    result
 }
 
-fn range(from_v: Coord, to_v: Coord) -> Vec<Coord> {
-    let mut result = vec![];
-    let mut offset = 0;
-    if from_v.0 == to_v.0 {
-        loop {
-            let step: Coord = (from_v.0, from_v.1 + offset).into();
-            result.push(step);
-            if step == to_v {
-                break;
+fn read_paths(input: &str) -> Result<Vec<Vec<Point>>> {
+    let mut paths = vec![];
+    for line in numbered_lines(input)? {
+        let mut path: Vec<Point> = vec![];
+        for vertex in line.text.split(" -> ") {
+            let coord = parse_point(&line, vertex)?;
+            if let Some(last) = path.last() {
+                if last.x != coord.x && last.y != coord.y {
+                    return Err(line.field_error(vertex, "segments must be horizontal or vertical"));
+                }
             }
-            offset += (to_v.1 - from_v.1).signum();
-        }
-    } else if from_v.1 == to_v.1 {
-        loop {
-            let step: Coord = (from_v.0 + offset, from_v.1).into();
-            result.push(step);
-            if step == to_v {
-                break;
+            if coord.y < 0 {
+                return Err(line.field_error(vertex, "rock must be below the sand source"));
             }
-            offset += (to_v.0 - from_v.0).signum();
+            path.push(coord);
         }
-    } else {
-        panic!("Coords must match in one axis")
+        paths.push(path);
     }
+    Ok(paths)
+}
 
-    result
+pub struct Scan {
+    paths: Vec<Vec<Point>>,
+    // Where the sand pours in
+    source: Point,
 }
 
 struct Cave {
-    grid: HashSet<Coord>,
+    // Filled with rock or sand
+    grid: Grid<bool>,
+    // x coordinate of the grid's first column
+    min_x: i32,
     max_y: i32,
 }
 
 impl Cave {
-    fn new(filename: &str, part_2: bool) -> Self {
-        let mut grid = HashSet::<Coord>::new();
+    fn new(paths: &[Vec<Point>], source: Point, part_2: bool) -> Self {
         let mut max_y = 0;
-        for line in read_list(filename) {
-            let vertices = line.split(" -> ");
+        for vert in paths.iter().flatten() {
+            max_y = max_y.max(vert.y);
+        }
+        // Since max slope is y = x, sand can spread at most floor_y either
+        // side of the source; leave room for the floor and any rock beyond
+        // that.
+        let floor_y = max_y + 2;
+        let min_x = paths
+            .iter()
+            .flatten()
+            .map(|v| v.x)
+            .fold(source.x - floor_y, i32::min);
+        let max_x = paths
+            .iter()
+            .flatten()
+            .map(|v| v.x)
+            .fold(source.x + floor_y, i32::max);
+        let width = (max_x - min_x + 1) as usize;
+        let mut cave = Self {
+            grid: Grid::new(width, floor_y as usize + 1, false),
+            min_x,
+            max_y,
+        };
+
+        for vertices in paths {
             let mut last_vert = None;
-            for vert in vertices {
+            for &vert in vertices {
                 if last_vert.is_none() {
                     last_vert = Some(vert);
                 }
-                let from_v = last_vert.unwrap().into();
-                let to_v = vert.into();
+                let from_v = last_vert.unwrap();
+                let to_v = vert;
                 for cell in range(from_v, to_v) {
-                    grid.insert(cell);
-                    if cell.1 > max_y {
-                        max_y = cell.1;
-                    }
+                    cave.fill(cell);
                 }
                 last_vert = Some(vert);
             }
         }
         if part_2 {
             // "Infinite" floor two beyond max_y.
-            // Since max slope is y = x, can emulate with just max_y either side of the starting x value (500)
-            max_y += 2;
-            for cell in range((500 - max_y, max_y).into(), (500 + max_y, max_y).into()) {
-                grid.insert(cell);
+            cave.max_y = floor_y;
+            for cell in range(Point::new(min_x, floor_y), Point::new(max_x, floor_y)) {
+                cave.fill(cell);
             }
         }
-        Self { grid, max_y }
+        cave
+    }
+
+    fn pos(&self, c: Point) -> Option<Pos> {
+        self.grid.checked_pos(c.y as i64, (c.x - self.min_x) as i64)
+    }
+
+    fn filled(&self, c: Point) -> bool {
+        self.pos(c).map(|pos| self.grid[pos]) == Some(true)
+    }
+
+    fn fill(&mut self, c: Point) {
+        let pos = self.pos(c).expect("cave grid covers all rock and sand");
+        self.grid[pos] = true;
     }
 
-    fn drop_sand(&mut self, start: Coord) -> bool {
+    fn drop_sand(&mut self, start: Point) -> bool {
         // from Part Two - blocking the entrance also stops things
-        if self.grid.contains(&start) {
+        if self.filled(start) {
             return false;
         }
 
+        // Straight down, then down and to the left, then down and to the right
+        const FALLS: [Point; 3] = [Point::new(0, 1), Point::new(-1, 1), Point::new(1, 1)];
         let mut pos = start;
         loop {
-            if pos.1 > self.max_y {
+            if pos.y > self.max_y {
                 // did not come to rest
                 return false;
             }
-            if !self.grid.contains(&(pos.0, pos.1 + 1).into()) {
-                // move down one unit
-                pos = (pos.0, pos.1 + 1).into();
-            } else if !self.grid.contains(&(pos.0 - 1, pos.1 + 1).into()) {
-                // move down one and one to the left
-                pos = (pos.0 - 1, pos.1 + 1).into();
-            } else if !self.grid.contains(&(pos.0 + 1, pos.1 + 1).into()) {
-                // move down one and one to the right
-                pos = (pos.0 + 1, pos.1 + 1).into();
-            } else {
-                // sand has come to rest.
-                self.grid.insert(pos);
-                return true;
+            match FALLS
+                .iter()
+                .map(|&fall| pos + fall)
+                .find(|&p| !self.filled(p))
+            {
+                Some(next) => pos = next,
+                None => {
+                    // sand has come to rest.
+                    self.fill(pos);
+                    return true;
+                }
             }
         }
     }
 }
 
-pub fn step1() {
-    let mut cave = Cave::new("inputs/day14.txt", false);
-    let mut count = 0;
-    while cave.drop_sand(Coord::from((500, 0))) {
-        count += 1;
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = Scan;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day14.txt");
+    const PARAMS: &'static [Param] = &[Param {
+        name: "source_x",
+        default: "500",
+        example: None,
+    }];
+
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        Ok(Scan {
+            paths: read_paths(input)?,
+            source: Point::new(params.get("source_x")?, 0),
+        })
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        let mut cave = Cave::new(&input.paths, input.source, false);
+        let mut count = 0;
+        while cave.drop_sand(input.source) {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        let mut cave = Cave::new(&input.paths, input.source, true);
+        let mut count = 0;
+        while cave.drop_sand(input.source) {
+            count += 1;
+        }
+        Ok(count)
     }
 
-    println!("Number of sand grains: {}", count);
+    // `size` paths of rock below the source
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let spread = 10 + size as i64;
+        let mut input = String::new();
+        for _ in 0..size.max(1) {
+            let (mut x, mut y) = (
+                rng.range(500 - spread..=500 + spread),
+                rng.range(1..=spread),
+            );
+            let mut vertices = vec![format!("{},{}", x, y)];
+            // Alternately across and down, so each segment is straight
+            for idx in 0..rng.range(1..=4) {
+                let step = rng.range(1..=8) * if rng.chance(50) { 1 } else { -1 };
+                if idx % 2 == 0 {
+                    x += step;
+                } else {
+                    y = (y + step).max(1);
+                }
+                vertices.push(format!("{},{}", x, y));
+            }
+            input += &(vertices.join(" -> ") + "\n");
+        }
+        Generated::new(input)
+    }
 }
 
-pub fn step2() {
-    let mut cave = Cave::new("inputs/day14.txt", true);
-    let mut count = 0;
-    while cave.drop_sand(Coord::from((500, 0))) {
-        count += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day14::part1(&example_input::<Day14>()).unwrap(), 24);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day14::part2(&example_input::<Day14>()).unwrap(), 93);
     }
 
-    println!("Number of sand grains (part 2): {}", count);
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day14>(14);
+        assert_eq!(Day14::part1(&input).unwrap(), 614);
+        assert_eq!(Day14::part2(&input).unwrap(), 26170);
+    }
 }
@@ -1,112 +1,102 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+use std::collections::HashSet;
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Coord {
-    x: i32,
-    y: i32,
-}
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    geometry::Point,
+    input::numbered_lines,
+    params::{Param, Params},
+    solution::{Reference, Solution},
+};
 
 #[derive(Debug)]
-struct Sensor {
-    position: Coord,
-    closest_beacon: Coord,
+pub struct Sensor {
+    position: Point,
+    closest_beacon: Point,
 }
 
 impl Sensor {
-    fn possible_beacon(&self, b: Coord) -> bool {
-        let min_dist = (self.closest_beacon.x - self.position.x).abs()
-            + (self.closest_beacon.y - self.position.y).abs();
+    fn possible_beacon(&self, b: Point) -> bool {
+        let min_dist = self.position.manhattan(self.closest_beacon);
         // Could there be a beacon at the given coord - i.e. is it further than min_dist?
-        let b_dist = (b.x - self.position.x).abs() + (b.y - self.position.y).abs();
-        b_dist > min_dist
+        self.position.manhattan(b) > min_dist
     }
 
-    fn boundary(&self) -> Vec<Coord> {
+    fn boundary(&self) -> Vec<Point> {
         let mut result = vec![];
-        let min_dist = (self.closest_beacon.x - self.position.x).abs()
-            + (self.closest_beacon.y - self.position.y).abs();
+        let min_dist = self.position.manhattan(self.closest_beacon);
 
         for offset in 0..=(min_dist + 1) {
-            result.push(Coord {
-                x: self.position.x + offset,
-                y: self.position.y + (1 + min_dist - offset),
-            });
-            result.push(Coord {
-                x: self.position.x + offset,
-                y: self.position.y - (1 + min_dist - offset),
-            });
-            result.push(Coord {
-                x: self.position.x - offset,
-                y: self.position.y + (1 + min_dist - offset),
-            });
-            result.push(Coord {
-                x: self.position.x - offset,
-                y: self.position.y - (1 + min_dist - offset),
-            });
+            let rest = 1 + min_dist - offset;
+            for (dx, dy) in [
+                (offset, rest),
+                (offset, -rest),
+                (-offset, rest),
+                (-offset, -rest),
+            ] {
+                result.push(self.position + Point::new(dx, dy));
+            }
         }
 
         result
     }
 }
 
-struct SensorMap {
+pub struct SensorMap {
     sensors: Vec<Sensor>,
-    beacons: HashSet<Coord>,
+    beacons: HashSet<Point>,
+    // Row checked in part 1
+    row: i32,
+    // Largest coordinate the distress beacon can have
+    bound: i32,
 }
 
 impl SensorMap {
-    fn new(filename: &str) -> Self {
+    fn new(input: &str, row: i32, bound: i32) -> Result<Self> {
         let mut sensors: Vec<Sensor> = vec![];
-        let mut beacons: HashSet<Coord> = HashSet::new();
-        for line in read_list(filename) {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-
-            let s_x: i32 = parts[2]
-                .split('=')
-                .nth(1)
-                .unwrap()
-                .strip_suffix(',')
-                .unwrap()
-                .parse()
-                .unwrap();
-            let s_y: i32 = parts[3]
-                .split('=')
-                .nth(1)
-                .unwrap()
-                .strip_suffix(':')
-                .unwrap()
-                .parse()
-                .unwrap();
-            let b_x: i32 = parts[8]
-                .split('=')
-                .nth(1)
-                .unwrap()
-                .strip_suffix(',')
-                .unwrap()
-                .parse()
-                .unwrap();
-            let b_y: i32 = parts[9].split('=').nth(1).unwrap().parse().unwrap();
+        let mut beacons: HashSet<Point> = HashSet::new();
+        for line in numbered_lines(input)? {
+            // "Sensor at x=2, y=18: closest beacon is at x=-2, y=15"
+            let parts: Vec<&str> = line.text.split_whitespace().collect();
+            if parts.len() != 10 || parts[0] != "Sensor" || parts[4] != "closest" {
+                return Err(line.error(
+                    "expected 'Sensor at x=<x>, y=<y>: closest beacon is at x=<x>, y=<y>'",
+                ));
+            }
+            // The value of an "x=2," style field, without its punctuation
+            let value = |idx: usize, name: &str, suffix: &str| -> Result<i32> {
+                let field = parts[idx];
+                match field
+                    .strip_prefix(name)
+                    .and_then(|f| f.strip_prefix('='))
+                    .map(|f| f.strip_suffix(suffix).unwrap_or(f))
+                {
+                    Some(value) => line.parse(value),
+                    None => Err(line.field_error(field, format!("expected '{}=<value>'", name))),
+                }
+            };
+
+            let s_x = value(2, "x", ",")?;
+            let s_y = value(3, "y", ":")?;
+            let b_x = value(8, "x", ",")?;
+            let b_y = value(9, "y", "")?;
 
             sensors.push(Sensor {
-                position: Coord { x: s_x, y: s_y },
-                closest_beacon: Coord { x: b_x, y: b_y },
+                position: Point::new(s_x, s_y),
+                closest_beacon: Point::new(b_x, b_y),
             });
-            beacons.insert(Coord { x: b_x, y: b_y });
+            beacons.insert(Point::new(b_x, b_y));
         }
 
-        Self { sensors, beacons }
+        Ok(Self {
+            sensors,
+            beacons,
+            row,
+            bound,
+        })
     }
 
-    fn possible_beacon(&self, c: Coord) -> bool {
+    fn possible_beacon(&self, c: Point) -> bool {
         for s in &self.sensors {
             if !s.possible_beacon(c) && !self.beacons.contains(&c) {
                 return false;
@@ -114,47 +104,219 @@ impl SensorMap {
         }
         true
     }
-}
 
-pub fn step1() {
-    let sensor_map = SensorMap::new("inputs/day15.txt");
+    // Every position on the row some sensor rules out, one at a time.
+    fn reference_part1(&self) -> i32 {
+        let mut ruled_out = HashSet::new();
+        for s in &self.sensors {
+            let reach = s.position.manhattan(s.closest_beacon);
+            let half_width = reach - (s.position.y - self.row).abs();
+            for x in s.position.x - half_width..=s.position.x + half_width {
+                ruled_out.insert(x);
+            }
+        }
+        for beacon in &self.beacons {
+            if beacon.y == self.row {
+                ruled_out.remove(&beacon.x);
+            }
+        }
+        ruled_out.len() as i32
+    }
 
-    let mut beaconless_count = 0;
-    for pos in -10000000..10000000 {
-        if !sensor_map.possible_beacon(Coord { x: pos, y: 2000000 }) {
-            beaconless_count += 1;
+    // Every position within the bound, one at a time.
+    fn reference_part2(&self) -> i64 {
+        for x in 0..=self.bound {
+            for y in 0..=self.bound {
+                let pos = Point::new(x, y);
+                let out_of_reach = self
+                    .sensors
+                    .iter()
+                    .all(|s| s.position.manhattan(pos) > s.position.manhattan(s.closest_beacon));
+                if out_of_reach && !self.beacons.contains(&pos) {
+                    return x as i64 * 4000000 + y as i64;
+                }
+            }
         }
+        panic!("No distress beacon found");
     }
+}
+
+// The first position along the row which no sensor rules out, other than
+// the distress beacon, if any.
+fn first_gap(sensors: &[(Point, i32)], y: i32, bound: i32, beacon: Point) -> Option<i32> {
+    let mut covered: Vec<(i32, i32)> = sensors
+        .iter()
+        .filter_map(|&(position, reach)| {
+            let half_width = reach - (position.y - y).abs();
+            (half_width >= 0).then_some((position.x - half_width, position.x + half_width))
+        })
+        .collect();
+    if beacon.y == y {
+        covered.push((beacon.x, beacon.x));
+    }
+    covered.sort_unstable();
+    let mut x = 0;
+    for (start, end) in covered {
+        if start > x {
+            break;
+        }
+        x = x.max(end + 1);
+    }
+    (x <= bound).then_some(x)
+}
 
-    println!("{}", beaconless_count);
+// Sensors ruling out everywhere from 0 to `bound` in both directions but
+// the distress beacon, starting with `count` placed at random.
+fn generate_sensors(rng: &mut Rng, count: usize, bound: i32) -> String {
+    let random_point = |rng: &mut Rng| {
+        let mut coord = || rng.range(0..=bound as i64) as i32;
+        Point::new(coord(), coord())
+    };
+    let beacon = random_point(rng);
+    // Each reaches as far as it can without finding the distress beacon
+    let reach = |position: Point| position.manhattan(beacon) - 1;
+    let mut sensors = vec![];
+    for _ in 0..count {
+        let position = random_point(rng);
+        if reach(position) > 0 {
+            sensors.push((position, reach(position)));
+        }
+    }
+    for y in 0..=bound {
+        while let Some(x) = first_gap(&sensors, y, bound, beacon) {
+            // One step further from the distress beacon than the gap, so
+            // it reaches far enough to cover it
+            let gap = Point::new(x, y);
+            let position = gap + (gap - beacon).signum();
+            sensors.push((position, reach(position)));
+        }
+    }
+    let mut input = String::new();
+    for (position, reach) in sensors {
+        let dx = rng.range(0..=reach as i64) as i32;
+        let offset = Point::new(
+            if rng.chance(50) { dx } else { -dx },
+            if rng.chance(50) {
+                reach - dx
+            } else {
+                dx - reach
+            },
+        );
+        let closest = position + offset;
+        input += &format!(
+            "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+            position.x, position.y, closest.x, closest.y
+        );
+    }
+    input
 }
 
-pub fn step2() {
-    let sensor_map = SensorMap::new("inputs/day15.txt");
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = SensorMap;
+    type Answer1 = i32;
+    type Answer2 = i64;
 
-    // If there is only a single position for the distress beacon, it must be
-    // adjacent to a 'no-go' area, therefore must be on the 'boundary' one
-    // beyond min-dist of any sensor. Collect all the boundaries together,
-    // then traverse this checking for possible beacon positions.
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day15.txt");
+    const PARAMS: &'static [Param] = &[
+        Param {
+            name: "row",
+            default: "2000000",
+            example: Some("10"),
+        },
+        Param {
+            name: "bound",
+            default: "4000000",
+            example: Some("20"),
+        },
+    ];
+    const REFERENCE1: Option<Reference<Self::Input, i32>> = Some(SensorMap::reference_part1);
+    const REFERENCE2: Option<Reference<Self::Input, i64>> = Some(SensorMap::reference_part2);
 
-    // Note we could have (a tiny proportion of) duplicates in here, but a
-    // Vec is much faster than a set, and this doesn't affect correctness.
-    let mut full_boundary: Vec<Coord> = vec![];
-    for s in &sensor_map.sensors {
-        full_boundary.extend(s.boundary().iter());
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        SensorMap::new(input, params.get("row")?, params.get("bound")?)
     }
 
-    for pos in full_boundary {
-        if pos.x < 0 || pos.y < 0 || pos.x > 4000000 || pos.y > 4000000 {
-            continue;
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Only positions some sensor reaches can be ruled out
+        let reach = |s: &Sensor| s.position.manhattan(s.closest_beacon);
+        let min_x = input.sensors.iter().map(|s| s.position.x - reach(s)).min();
+        let max_x = input.sensors.iter().map(|s| s.position.x + reach(s)).max();
+        let mut beaconless_count = 0;
+        for pos in min_x.unwrap_or(0)..=max_x.unwrap_or(-1) {
+            if !input.possible_beacon(Point::new(pos, input.row)) {
+                beaconless_count += 1;
+            }
         }
-        if sensor_map.possible_beacon(pos) {
-            println!("Distress beacon at {:?}", pos);
-            println!(
-                "Tuning frequency: {}",
-                pos.x as i64 * 4000000 + pos.y as i64
-            );
-            break;
+        Ok(beaconless_count)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        // If there is only a single position for the distress beacon, it must be
+        // adjacent to a 'no-go' area, therefore must be on the 'boundary' one
+        // beyond min-dist of any sensor. Collect all the boundaries together,
+        // then traverse this checking for possible beacon positions.
+
+        // Note we could have (a tiny proportion of) duplicates in here, but a
+        // Vec is much faster than a set, and this doesn't affect correctness.
+        let mut full_boundary: Vec<Point> = vec![];
+        for s in &input.sensors {
+            full_boundary.extend(s.boundary().iter());
         }
+
+        for pos in full_boundary {
+            if pos.x < 0 || pos.y < 0 || pos.x > input.bound || pos.y > input.bound {
+                continue;
+            }
+            // Known beacons pass the check, but can't be the distress beacon
+            if input.possible_beacon(pos) && !input.beacons.contains(&pos) {
+                crate::info!("Distress beacon at {}", pos);
+                return Ok(pos.x as i64 * 4000000 + pos.y as i64);
+            }
+        }
+        Err(Error::new("no distress beacon found"))
+    }
+
+    // `size` random sensors, plus those needed to leave only one place for
+    // the distress beacon within a bound of 20 times the size
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let bound = 20 * size.max(1) as i32;
+        let row = rng.range(0..=bound as i64);
+        Generated::new(generate_sensors(rng, size, bound))
+            .with_param("row", row)
+            .with_param("bound", bound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day15::part1(&example_input::<Day15>()).unwrap(), 26);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day15::part2(&example_input::<Day15>()).unwrap(), 56000011);
+    }
+
+    #[test]
+    fn no_distress_beacon() {
+        let mut params = Params::new(Day15::PARAMS, true);
+        params.set("bound", "3").unwrap();
+        let sensors = Day15::parse(Day15::EXAMPLE, &params).unwrap();
+        assert!(Day15::part2(&sensors).is_err());
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day15>(15);
+        assert_eq!(Day15::part1(&input).unwrap(), 4560025);
+        assert_eq!(Day15::part2(&input).unwrap(), 12480406634249);
     }
 }
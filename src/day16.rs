@@ -1,19 +1,16 @@
 use std::{
     collections::{HashMap, HashSet},
-    fs::File,
-    io::{BufRead, BufReader},
+    fmt::Write,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum ValveState {
-    Opened,
-    Closed,
-}
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::{Param, Params},
+    search::{dijkstra, Graph},
+    solution::{Reference, Solution},
+};
 
 #[derive(Debug, Clone)]
 struct Valve {
@@ -22,58 +19,84 @@ struct Valve {
 }
 
 #[derive(Debug)]
-struct Volcano {
+pub struct Volcano {
     valves: HashMap<String, Valve>,
     distance: HashMap<(String, String), i32>,
+    // Where we (and the elephant) begin
+    start: String,
+    // The valves as parsed, before reduce() removes those without flow
+    original: HashMap<String, Valve>,
 }
 
 impl Volcano {
-    fn new(filename: &str) -> Self {
+    fn new(input: &str, start: &str) -> Result<Self> {
         let mut valves = HashMap::new();
-        for line in read_list(filename) {
+        let lines = numbered_lines(input)?;
+        for line in &lines {
+            // "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB"
             let mut tunnels = HashMap::new();
-            let mut parts = line.split_whitespace();
-            let name = parts.nth(1).unwrap().to_string();
-            let flow_rate: i32 = parts
-                .nth(2)
-                .unwrap()
-                .trim_start_matches("rate=")
-                .trim_end_matches(';')
-                .parse()
-                .unwrap();
-            let _ = parts.nth(3); // advance_by is unstable
-            for tunnel in parts {
+            let parts: Vec<&str> = line.text.split_whitespace().collect();
+            if parts.len() < 10 || parts[0] != "Valve" || !parts[4].starts_with("rate=") {
+                return Err(line.error(
+                    "expected 'Valve <name> has flow rate=<rate>; tunnels lead to valves <names>'",
+                ));
+            }
+            let name = parts[1].to_string();
+            let rate = parts[4].trim_start_matches("rate=").trim_end_matches(';');
+            let flow_rate: i32 = line.parse(rate)?;
+            for tunnel in &parts[9..] {
                 let tun_name = tunnel.trim_end_matches(',');
                 tunnels.insert(tun_name.to_string(), 1);
             }
-            valves.insert(name.clone(), Valve { flow_rate, tunnels });
+            if valves
+                .insert(name.clone(), Valve { flow_rate, tunnels })
+                .is_some()
+            {
+                return Err(line.field_error(parts[1], "duplicate valve"));
+            }
+        }
+        // Every tunnel must lead somewhere, including to the start
+        for line in &lines {
+            for tunnel in line.text.split_whitespace().skip(9) {
+                if !valves.contains_key(tunnel.trim_end_matches(',')) {
+                    return Err(line.field_error(tunnel, "unknown valve"));
+                }
+            }
         }
-        Self {
+        if !valves.contains_key(start) {
+            return Err(Error::new(format!("no starting valve '{}'", start)));
+        }
+        Ok(Self {
+            original: valves.clone(),
             valves,
             distance: HashMap::new(),
-        }
+            start: start.to_string(),
+        })
     }
 
-    #[allow(dead_code)]
-    fn render_dot(&self) {
-        println!("graph {{");
-        println!("overlap=scale");
-        println!("layout=neato");
-        println!("graph [size=10]");
-        println!("edge [dir=none,len=2]");
+    // A Graphviz description of the valves and tunnels between them.
+    fn render_dot(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "graph {{").unwrap();
+        writeln!(out, "overlap=scale").unwrap();
+        writeln!(out, "layout=neato").unwrap();
+        writeln!(out, "graph [size=10]").unwrap();
+        writeln!(out, "edge [dir=none,len=2]").unwrap();
 
         let mut edges_seen = HashSet::new();
 
         for valve in &self.valves {
-            if valve.0 == "AA" {
-                println!("\"{}\" [color=\"#0000ff\",shape=\"rect\"]", valve.0);
+            if *valve.0 == self.start {
+                writeln!(out, "\"{}\" [color=\"#0000ff\",shape=\"rect\"]", valve.0).unwrap();
             } else if valve.1.flow_rate != 0 {
-                println!(
+                writeln!(
+                    out,
                     "\"{}\" [color=\"#ff0000\",label=\"{}:{}\"]",
                     valve.0, valve.0, valve.1.flow_rate
-                );
+                )
+                .unwrap();
             } else {
-                println!("\"{}\" [shape=\"none\"]", valve.0);
+                writeln!(out, "\"{}\" [shape=\"none\"]", valve.0).unwrap();
             }
         }
 
@@ -83,14 +106,15 @@ impl Volcano {
                     && !edges_seen.contains(&(edge.0, valve.0))
                 {
                     edges_seen.insert((valve.0, edge.0));
-                    print!("{} -- {} [label=\"{}\"]", valve.0, edge.0, edge.1);
+                    writeln!(out, "{} -- {} [label=\"{}\"]", valve.0, edge.0, edge.1).unwrap();
                 }
             }
         }
-        println!("}}");
+        writeln!(out, "}}").unwrap();
+        out
     }
 
-    fn neighbours(&self, pos: String) -> Vec<(String, i32)> {
+    fn tunnels(&self, pos: String) -> Vec<(String, i32)> {
         let mut result = vec![];
         for n in &self.valves[&pos].tunnels {
             result.push((n.0.clone(), *n.1));
@@ -99,16 +123,19 @@ impl Volcano {
     }
 
     fn remove_pos(&mut self, pos: String) {
-        for source in self.neighbours(pos.clone()) {
-            for target in self.neighbours(pos.clone()) {
+        for source in self.tunnels(pos.clone()) {
+            for target in self.tunnels(pos.clone()) {
                 if source.0 == target.0 {
                     continue;
                 }
+                // Keep any shorter tunnel there already was
                 self.valves
                     .get_mut(&source.0)
                     .unwrap()
                     .tunnels
-                    .insert(target.0.clone(), source.1 + target.1);
+                    .entry(target.0.clone())
+                    .and_modify(|d| *d = (*d).min(source.1 + target.1))
+                    .or_insert(source.1 + target.1);
                 self.valves
                     .get_mut(&target.clone().0)
                     .unwrap()
@@ -128,7 +155,7 @@ impl Volcano {
         let to_remove: Vec<String> = self
             .valves
             .iter()
-            .filter(|v| v.0 != "AA" && v.1.flow_rate == 0)
+            .filter(|v| *v.0 != self.start && v.1.flow_rate == 0)
             .map(|x| x.0.clone())
             .collect();
         for doomed in to_remove {
@@ -137,168 +164,327 @@ impl Volcano {
     }
 
     fn calc_all_pairs_shortest(&mut self) {
-        // See https://en.wikipedia.org/wiki/Floyd–Warshall_algorithm
         let mut dist: HashMap<(String, String), i32> = HashMap::new();
-        let names: Vec<String> = self.valves.iter().map(|v| v.0.clone()).collect();
-        // initialise all pairs to a big number
-        for vn_i in &names {
-            for vn_j in &names {
-                // 'infinity', but use a somewhat lower value to avoid i32 overflow.
-                dist.insert((vn_i.clone(), vn_j.clone()), 1000000);
+        for from in self.valves.keys() {
+            let search = dijkstra(self, [from.clone()], |_| false);
+            for (to, distance) in search.distances() {
+                dist.insert((from.clone(), to.clone()), *distance as i32);
             }
         }
-        for v in &self.valves {
-            for t in &v.1.tunnels {
-                // fill in the known neighbour distances
-                dist.insert((v.0.clone(), t.0.clone()), *t.1);
-            }
-            // Distance to self is null
-            dist.insert((v.0.clone(), v.0.clone()), 0);
-        }
+        self.distance = dist;
+    }
 
-        for vn_i in &names {
-            for vn_j in &names {
-                for vn_k in &names {
-                    let ik_kj_dist =
-                        dist[&(vn_i.clone(), vn_k.clone())] + dist[&(vn_k.clone(), vn_j.clone())];
-                    if dist[&(vn_i.clone(), vn_j.clone())] > ik_kj_dist {
-                        dist.insert((vn_i.clone(), vn_j.clone()), ik_kj_dist);
-                    }
-                }
-            }
-        }
+    /// Most pressure one person can release in `remain` minutes.
+    pub fn calculate(&self, remain: i32) -> i32 {
+        self.best_per_valve_set(remain)
+            .into_values()
+            .max()
+            .unwrap_or(0)
+    }
 
-        self.distance = dist;
+    // The most pressure one person can release in `remain` minutes for
+    // each set of valves they could open, as a bitmask over `working`.
+    fn best_per_valve_set(&self, remain: i32) -> HashMap<u64, i32> {
+        let working: Vec<&String> = self
+            .valves
+            .iter()
+            .filter(|v| v.1.flow_rate > 0)
+            .map(|v| v.0)
+            .collect();
+        let mut best = HashMap::new();
+        self.explore(&working, &self.start, remain, 0, 0, &mut best);
+        best
     }
 
-    fn brute_force(
+    fn explore(
         &self,
+        working: &[&String],
+        pos: &str,
         remain: i32,
-        current_pos: String,
-        valve_state: HashMap<String, ValveState>,
-    ) -> i32 {
-        if remain < 0 {
-            return 0;
-        }
-        if valve_state.iter().all(|v| v.1 == &ValveState::Opened) {
-            return 0;
+        opened: u64,
+        pressure: i32,
+        best: &mut HashMap<u64, i32>,
+    ) {
+        let entry = best.entry(opened).or_insert(pressure);
+        *entry = (*entry).max(pressure);
+        for (idx, &valve) in working.iter().enumerate() {
+            if opened & (1 << idx) != 0 {
+                continue;
+            }
+            // Valves with no route from here are never worth heading for
+            let Some(distance) = self.distance.get(&(pos.to_string(), valve.clone())) else {
+                continue;
+            };
+            // +1 minute to open the valve...
+            let left = remain - distance - 1;
+            if left <= 0 {
+                continue;
+            }
+            let released = self.valves[valve].flow_rate * left;
+            self.explore(
+                working,
+                valve,
+                left,
+                opened | (1 << idx),
+                pressure + released,
+                best,
+            );
         }
+    }
+
+    /// Most pressure released by two people working together.
+    pub fn calculate_joint(&self, remain: i32) -> i32 {
+        // Each opens a different set of valves, so pair up the best for
+        // every two disjoint sets, most pressure first so the search can
+        // stop once no pair can beat the best so far
+        let mut sets: Vec<(u64, i32)> = self.best_per_valve_set(remain).into_iter().collect();
+        sets.sort_by_key(|&(_, pressure)| std::cmp::Reverse(pressure));
+        crate::info!("Pairing up {} sets of valves", sets.len());
 
         let mut best_pressure = 0;
-        for next_check in &valve_state {
-            let valve_name = next_check.0;
-            let valve_open = valve_state.get(valve_name).unwrap();
-            if valve_open == &ValveState::Opened {
-                continue;
+        for (idx, &(mine, my_pressure)) in sets.iter().enumerate() {
+            if my_pressure * 2 <= best_pressure {
+                break;
             }
-            let flow_rate = self.valves.get(valve_name).unwrap().flow_rate;
-            let mut candidate = valve_state.clone();
-            candidate.insert(valve_name.clone(), ValveState::Opened);
-            // +1 minute to open the valve...
-            let time_cost = self
-                .distance
-                .get(&(current_pos.clone(), valve_name.clone()))
-                .unwrap()
-                + 1;
-            let extra_pressure = flow_rate * (remain - time_cost);
-            let valve_calc = self.brute_force(remain - time_cost, valve_name.clone(), candidate);
-            if valve_calc + extra_pressure > best_pressure {
-                best_pressure = valve_calc + extra_pressure;
+            for &(elephants, elephant_pressure) in &sets[idx..] {
+                if my_pressure + elephant_pressure <= best_pressure {
+                    break;
+                }
+                if mine & elephants == 0 {
+                    best_pressure = my_pressure + elephant_pressure;
+                }
             }
         }
         best_pressure
     }
 
-    fn calculate(&self, remain: i32) -> i32 {
-        let mut valve_state: HashMap<String, ValveState> = self
-            .valves
+    /// Most pressure released in `remain` minutes by `openers` people,
+    /// trying every move each could make each minute on the tunnels as
+    /// parsed. Far too slow for the real input, and limited to 128 valves,
+    /// but shares nothing with the fast solution beyond parsing.
+    pub fn reference(&self, remain: i32, openers: usize) -> i32 {
+        let names: Vec<&String> = self.original.keys().collect();
+        let rates: Vec<i32> = names.iter().map(|n| self.original[*n].flow_rate).collect();
+        let tunnels: Vec<Vec<usize>> = names
             .iter()
-            .map(|v| (v.0.clone(), ValveState::Closed))
+            .map(|n| {
+                let tunnels = &self.original[*n].tunnels;
+                (0..names.len())
+                    .filter(|&idx| tunnels.contains_key(names[idx]))
+                    .collect()
+            })
             .collect();
-        valve_state.insert("AA".to_string(), ValveState::Opened);
-        self.brute_force(remain, "AA".to_string(), valve_state)
-    }
+        let start = names.iter().position(|n| **n == self.start).unwrap();
 
-    fn all_partitions(&self) -> Vec<HashSet<String>> {
-        let mut result = vec![];
-        let valves: Vec<String> = self.valves.iter().map(|v| v.0.clone()).collect();
-
-        // Only need to iterate over len(valves)-1 bits; leave the top bit always
-        // clear as inverse is identical a pattern we've had.
-        for part_idx in 0..(1 << (valves.len() - 1)) {
-            // Heuristic: splitting the work evenly is probably going to be best.
-            // Therefore with 15 valves, we want roughly half of those 15 bits to
-            // be each binary value. Check there's between 6 and 9 ones.
-            // Not only does this reduce the number of partitions, it also makes
-            // testing them much faster, as neither set of the partition has more
-            // than 9 valves to visit.
-            if (part_idx as i32).count_ones() < 6 || (part_idx as i32).count_ones() > 9 {
-                continue;
-            }
-            let mut partition = HashSet::new();
-            for (idx, valve) in valves.iter().enumerate() {
-                if (part_idx & (1 << idx)) != 0 {
-                    partition.insert(valve.clone());
+        // Best pressure for each position of the openers and set of open
+        // valves, as a bitmask, after each minute
+        let mut states: HashMap<(Vec<usize>, u128), i32> = HashMap::new();
+        states.insert((vec![start; openers], 0), 0);
+        for minute in 1..=remain {
+            let mut next_states = HashMap::new();
+            for ((positions, opened), pressure) in states {
+                // Every combination of what each opener does this minute
+                let mut choices = vec![(vec![], opened, pressure)];
+                for &pos in &positions {
+                    let mut extended = vec![];
+                    for (moved, opened, pressure) in choices {
+                        for &next in tunnels[pos].iter().chain([&pos]) {
+                            let mut moved = moved.clone();
+                            moved.push(next);
+                            extended.push((moved, opened, pressure));
+                        }
+                        if rates[pos] > 0 && opened & (1 << pos) == 0 {
+                            let mut moved = moved.clone();
+                            moved.push(pos);
+                            let released = rates[pos] * (remain - minute);
+                            extended.push((moved, opened | (1 << pos), pressure + released));
+                        }
+                    }
+                    choices = extended;
+                }
+                for (moved, opened, pressure) in choices {
+                    let best = next_states.entry((moved, opened)).or_insert(pressure);
+                    *best = (*best).max(pressure);
                 }
             }
-            result.push(partition);
+            states = next_states;
         }
+        states.into_values().max().unwrap_or(0)
+    }
+}
 
-        result
+// Tunnels between the remaining valves, which are longer once valves
+// without any flow have been removed.
+impl Graph for Volcano {
+    type Node = String;
+
+    fn neighbours(&self, pos: &String) -> Vec<String> {
+        self.valves[pos].tunnels.keys().cloned().collect()
     }
 
-    fn calculate_joint(&self, remain: i32) -> i32 {
-        let init_valve_state: HashMap<String, ValveState> = self
-            .valves
-            .iter()
-            .map(|v| (v.0.clone(), ValveState::Closed))
-            .collect();
+    fn cost(&self, from: &String, to: &String) -> usize {
+        self.valves[from].tunnels[to] as usize
+    }
+}
 
-        let mut best_pressure = 0;
+pub struct Day16;
 
-        let all_partitions = self.all_partitions();
-        //println!("Testing {} partitions", all_partitions.len());
+impl Solution for Day16 {
+    type Input = Volcano;
+    type Answer1 = i32;
+    type Answer2 = i32;
 
-        for (_idx, partition) in all_partitions.iter().enumerate() {
-            let mut my_valve_state = init_valve_state.clone();
-            let mut elephant_valve_state = init_valve_state.clone();
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day16.txt");
+    const PARAMS: &'static [Param] = &[Param {
+        name: "start",
+        default: "AA",
+        example: None,
+    }];
+    const REFERENCE1: Option<Reference<Self::Input, i32>> = Some(|input| input.reference(30, 1));
+    const REFERENCE2: Option<Reference<Self::Input, i32>> = Some(|input| input.reference(26, 2));
 
-            for valve_name in init_valve_state.keys() {
-                if partition.contains(valve_name) {
-                    my_valve_state.insert(valve_name.clone(), ValveState::Opened);
-                    elephant_valve_state.insert(valve_name.clone(), ValveState::Closed);
-                } else {
-                    my_valve_state.insert(valve_name.clone(), ValveState::Closed);
-                    elephant_valve_state.insert(valve_name.clone(), ValveState::Opened);
-                }
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        let mut volcano = Volcano::new(input, &params.get::<String>("start")?)?;
+        volcano.reduce();
+        // Sets of valves opened are bitmasks
+        let working = volcano.valves.values().filter(|v| v.flow_rate > 0).count();
+        if working > 64 {
+            return Err(Error::new(format!(
+                "at most 64 valves can have flow, not {}",
+                working
+            )));
+        }
+        crate::trace!("{}", volcano.render_dot());
+        volcano.calc_all_pairs_shortest();
+        Ok(volcano)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Best pressure in 30 minutes
+        Ok(input.calculate(30))
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        // With two valve openers in 26 minutes
+        Ok(input.calculate_joint(26))
+    }
+
+    // `size` valves, with up to fifteen of them working
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let count = size.clamp(2, 26 * 26);
+        let mut names = vec!["AA".to_string()];
+        while names.len() < count {
+            let name: String = (0..2)
+                .map(|_| (b'A' + rng.below(26) as u8) as char)
+                .collect();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        // A random tree joining every valve, with a few shortcuts
+        let mut tunnels: Vec<Vec<usize>> = vec![vec![]; count];
+        let mut join = |a: usize, b: usize| {
+            if a != b && !tunnels[a].contains(&b) {
+                tunnels[a].push(b);
+                tunnels[b].push(a);
             }
+        };
+        for idx in 1..count {
+            join(idx, rng.below(idx));
+        }
+        for _ in 0..count / 4 {
+            join(rng.below(count), rng.below(count));
+        }
+        let mut rates = vec![0; count];
+        let mut working: Vec<usize> = (1..count).collect();
+        rng.shuffle(&mut working);
+        for &idx in working.iter().take((count / 2).clamp(1, 15)) {
+            rates[idx] = rng.range(1..=25);
+        }
+        let mut order: Vec<usize> = (0..count).collect();
+        rng.shuffle(&mut order);
+        let mut input = String::new();
+        for idx in order {
+            let targets: Vec<&str> = tunnels[idx].iter().map(|&t| names[t].as_str()).collect();
+            let leads = match targets.len() {
+                1 => "tunnel leads to valve",
+                _ => "tunnels lead to valves",
+            };
+            input += &format!(
+                "Valve {} has flow rate={}; {} {}\n",
+                names[idx],
+                rates[idx],
+                leads,
+                targets.join(", ")
+            );
+        }
+        Generated::new(input)
+    }
+}
 
-            my_valve_state.insert("AA".to_string(), ValveState::Opened);
-            elephant_valve_state.insert("AA".to_string(), ValveState::Opened);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crosscheck::crosscheck,
+        solution::{committed_input, example_input, Part},
+    };
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day16::part1(&example_input::<Day16>()).unwrap(), 1651);
+    }
 
-            let partition_result = self.brute_force(remain, "AA".to_string(), my_valve_state)
-                + self.brute_force(remain, "AA".to_string(), elephant_valve_state);
-            if partition_result > best_pressure {
-                //println!("Partition# {}; best so far: {}", _idx, partition_result);
-                best_pressure = partition_result;
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day16::part2(&example_input::<Day16>()).unwrap(), 1707);
+    }
+
+    #[test]
+    fn reference_example() {
+        let input = example_input::<Day16>();
+        assert_eq!(input.reference(30, 1), 1651);
+        assert_eq!(input.reference(26, 2), 1707);
+    }
+
+    #[test]
+    fn agrees_with_reference() {
+        for seed in 0..5 {
+            let comparisons = crosscheck::<Day16>(seed, 6, Part::Both).unwrap();
+            assert_eq!(comparisons.len(), 2);
+            for comparison in comparisons {
+                assert!(comparison.agrees(), "seed {}: {:?}", seed, comparison);
             }
         }
+    }
 
-        best_pressure
+    #[test]
+    fn removed_valves_keep_shortest_tunnels() {
+        // Removing CC mustn't replace the direct tunnel from AA to BB
+        let input = "Valve AA has flow rate=0; tunnels lead to valves BB, CC\n\
+                     Valve BB has flow rate=10; tunnels lead to valves AA, CC\n\
+                     Valve CC has flow rate=0; tunnels lead to valves AA, BB\n";
+        let volcano = Day16::parse(input, &Params::new(Day16::PARAMS, false)).unwrap();
+        assert_eq!(Day16::part1(&volcano).unwrap(), 280);
     }
-}
 
-pub fn step1() {
-    let mut volcano = Volcano::new("inputs/day16.txt");
-    volcano.reduce();
-    //volcano.render_dot();
-    volcano.calc_all_pairs_shortest();
-    println!("Best pressure in 30 minutes: {}", volcano.calculate(30));
-}
+    #[test]
+    fn disconnected_valves() {
+        let input = "Valve AA has flow rate=0; tunnel leads to valve BB\n\
+                     Valve BB has flow rate=10; tunnel leads to valve AA\n\
+                     Valve CC has flow rate=20; tunnel leads to valve DD\n\
+                     Valve DD has flow rate=0; tunnel leads to valve CC\n";
+        let volcano = Day16::parse(input, &Params::new(Day16::PARAMS, false)).unwrap();
+        // CC can't be reached, so only BB is opened, at the end of minute 2
+        assert_eq!(Day16::part1(&volcano).unwrap(), 280);
+        assert_eq!(volcano.reference(30, 1), 280);
+    }
 
-pub fn step2() {
-    let mut volcano = Volcano::new("inputs/day16.txt");
-    volcano.reduce();
-    volcano.calc_all_pairs_shortest();
-    println!("With two valve openers in 26 minutes: {}", volcano.calculate_joint(26));
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day16>(16);
+        assert_eq!(Day16::part1(&input).unwrap(), 1720);
+        assert_eq!(Day16::part2(&input).unwrap(), 2582);
+    }
 }
@@ -1,15 +1,12 @@
-use std::{
-    fs::File,
-    io::{BufReader, Read},
+use crate::{
+    cycle::CycleDetector,
+    error::Result,
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::Params,
+    solution::Solution,
 };
 
-pub fn read_chars(filename: &str) -> Vec<char> {
-    let f = File::open(filename).expect("Could not read file");
-    let mut buffer = String::new();
-    BufReader::new(f).read_to_string(&mut buffer).unwrap();
-    return buffer.trim().chars().collect();
-}
-
 fn collide(chamber: &[u8], falling: &[u8], offset: usize) -> bool {
     for (idx, row) in falling.iter().enumerate() {
         if (chamber[offset + idx] & row) != 0 {
@@ -19,70 +16,93 @@ fn collide(chamber: &[u8], falling: &[u8], offset: usize) -> bool {
     false
 }
 
-#[allow(dead_code)]
-fn display(chamber: &[u8]) {
+// Top row first, with the floor below.
+fn render(chamber: &[u8]) -> String {
+    let mut out = String::new();
     for row in chamber.iter().rev() {
-        println!("{:07b}", row);
+        out += &format!("{:07b}\n", row);
     }
-    println!("-------\n");
+    out + "-------\n"
 }
 
-fn simulate(max_rocks: u64, wind: &[char], display: bool) -> usize {
+// Depth below the top of the pile to which the cycle key follows the open
+// cells. A gap which is never filled, such as a column the jets keep rocks
+// out of, would otherwise make the key grow forever. Anything deeper is
+// assumed not to affect later rocks, which holds for the puzzle's inputs but
+// isn't guaranteed: a rock slipping further down a narrow gap could land
+// differently in two states with the same key.
+const SURFACE_DEPTH: usize = 32;
+
+// Each rock's rows, lowest first; the high bit is the left wall side.
+const ROCKS: [&[u8]; 5] = [
+    &[0b0011110],
+    &[0b0001000, 0b0011100, 0b0001000],
+    // Note: this is opposite order to the shape
+    &[0b0011100, 0b0000100, 0b0000100],
+    &[0b0010000, 0b0010000, 0b0010000, 0b0010000],
+    &[0b0011000, 0b0011000],
+];
+
+struct Chamber {
     // Chamber represented as a vector of u8s where the high bit
-    // should always be zero. chamber[0] is row immediately above
+    // should always be zero. rows[0] is row immediately above
     // the ground.
-    let mut chamber: Vec<u8> = vec![];
-    let mut wind_idx = 0;
-    let mut rock_type = 0;
-    for rock_count in 0..max_rocks {
-        if display {
-            // We're looking for loops. Assume that when the base is nearly
-            // 'full', that's an interesting / likely point of repeat which
-            // is fairly rare.
-            if chamber.last().unwrap_or(&0u8).count_ones() > 5 {
-                println!(
-                    "{}: {} / {}: {} ## {}",
-                    rock_count,
-                    wind_idx,
-                    rock_type,
-                    chamber.last().unwrap_or(&0),
-                    chamber.len()
-                );
-            }
+    rows: Vec<u8>,
+    wind_idx: usize,
+    rock_type: usize,
+}
+
+impl Chamber {
+    fn new() -> Self {
+        Self {
+            rows: vec![],
+            wind_idx: 0,
+            rock_type: 0,
         }
+    }
 
-        let mut falling: Vec<u8> = vec![];
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
 
-        match rock_type {
-            0 => {
-                falling.push(0b0011110);
-            }
-            1 => {
-                falling.push(0b0001000);
-                falling.push(0b0011100);
-                falling.push(0b0001000);
-            }
-            2 => {
-                // Note: this is opposite order to the shape -
-                // lowest row is pushed first.
-                falling.push(0b0011100);
-                falling.push(0b0000100);
-                falling.push(0b0000100);
+    fn key(&self) -> (usize, usize, Vec<u8>) {
+        (self.rock_type, self.wind_idx, self.surface())
+    }
+
+    // The open cells which falling rocks can reach, as a row mask for each
+    // depth below the top, down to `SURFACE_DEPTH`. Rocks only move down and
+    // sideways through open cells, so cells sealed off under overhangs and
+    // full rows can't matter.
+    fn surface(&self) -> Vec<u8> {
+        let height = self.rows.len();
+        let open = |x: usize, y: usize| y >= height || self.rows[y] & (64 >> x) == 0;
+        let mut reached = vec![0u8; height.min(SURFACE_DEPTH) + 1];
+        let mut todo: Vec<(usize, usize)> = (0..7).map(|x| (x, height)).collect();
+        while let Some((x, y)) = todo.pop() {
+            let depth = height - y;
+            if !open(x, y) || reached[depth] & (64 >> x) != 0 {
+                continue;
             }
-            3 => {
-                falling.push(0b0010000);
-                falling.push(0b0010000);
-                falling.push(0b0010000);
-                falling.push(0b0010000);
+            reached[depth] |= 64 >> x;
+            if x > 0 {
+                todo.push((x - 1, y));
             }
-            4 => {
-                falling.push(0b0011000);
-                falling.push(0b0011000);
+            if x < 6 {
+                todo.push((x + 1, y));
             }
-            _ => {
-                panic!("Invalid rock type")
+            if y > 0 && depth < SURFACE_DEPTH {
+                todo.push((x, y - 1));
             }
         }
+        while reached.last() == Some(&0) {
+            reached.pop();
+        }
+        reached
+    }
+
+    fn drop_rock(&mut self, wind: &[char]) {
+        let mut falling: Vec<u8> = ROCKS[self.rock_type].to_vec();
+        let chamber = &mut self.rows;
 
         // Provide some extra air in our column to avoid special casing
         chamber.extend(vec![0; falling.len() + 3]);
@@ -93,7 +113,7 @@ fn simulate(max_rocks: u64, wind: &[char], display: bool) -> usize {
         loop {
             // Get blown by the wind
             let mut move_dir = 0;
-            if wind[wind_idx] == '<' {
+            if wind[self.wind_idx] == '<' {
                 if !falling.iter().any(|x| x & 64 != 0) {
                     move_dir = -1;
                 }
@@ -102,23 +122,23 @@ fn simulate(max_rocks: u64, wind: &[char], display: bool) -> usize {
             }
             if move_dir == -1 {
                 let candidate: Vec<u8> = falling.iter().map(|x| x << 1).collect();
-                if !collide(&chamber, &candidate, offset) {
+                if !collide(chamber, &candidate, offset) {
                     falling = candidate;
-                    //println!("Pushed left by wind");
+                    crate::trace!("Pushed left by wind");
                 }
             } else if move_dir == 1 {
                 let candidate: Vec<u8> = falling.iter().map(|x| x >> 1).collect();
-                if !collide(&chamber, &candidate, offset) {
+                if !collide(chamber, &candidate, offset) {
                     falling = candidate;
-                    //println!("Pushed right by wind");
+                    crate::trace!("Pushed right by wind");
                 }
             } else {
-                //println!("Can't push anymore");
+                crate::trace!("Can't push anymore");
             }
-            //display(&falling);
-            wind_idx = (wind_idx + 1) % wind.len();
+            crate::trace!("{}", render(&falling));
+            self.wind_idx = (self.wind_idx + 1) % wind.len();
 
-            if offset == 0 || collide(&chamber, &falling, offset - 1) {
+            if offset == 0 || collide(chamber, &falling, offset - 1) {
                 break;
             }
             offset -= 1;
@@ -135,53 +155,105 @@ fn simulate(max_rocks: u64, wind: &[char], display: bool) -> usize {
             chamber.pop();
         }
 
-        //println!("Resulting state:");
-        //display(&chamber);
+        crate::trace!("Resulting state:\n{}", render(chamber));
 
-        rock_type = (rock_type + 1) % 5;
+        self.rock_type = (self.rock_type + 1) % ROCKS.len();
     }
-    chamber.len()
 }
 
-pub fn step1() {
-    let wind = read_chars("inputs/day17.txt");
-    let height = simulate(2022, &wind, false);
-    println!("Height: {}", height);
+fn simulate(max_rocks: u64, wind: &[char]) -> usize {
+    let mut chamber = Chamber::new();
+    for _ in 0..max_rocks {
+        chamber.drop_rock(wind);
+    }
+    chamber.height()
 }
 
-pub fn step2() {
-    let wind = read_chars("inputs/day17.txt");
-
-    // Simulate enought to find loops...
-    //simulate(5000, &wind, true);
-
-    // Analyze loops - with human input!
-    // Output: (rock count / wind_idx / rock_type / row value ## height)
-    // 241: 1377 / 1: 63 ## 395
-    // 666: 3829 / 1: 63 ## 1063
-    // 836: 4780 / 1: 63 ## 1335
-    // 1276: 7380 / 1: 63 ## 2027
-    // 1986: 1377 / 1: 63 ## 3178
-    // 2411: 3829 / 1: 63 ## 3846
-    // 2581: 4780 / 1: 63 ## 4118
-    // 3021: 7380 / 1: 63 ## 4810
-    // 3731: 1377 / 1: 63 ## 5961
-    // 4156: 3829 / 1: 63 ## 6629
-    // 4326: 4780 / 1: 63 ## 6901
-    // 4766: 7380 / 1: 63 ## 7593
-    // So with wind_idx/rock_idx/row value all the same, we're going to loop
-    // and that happens every 1745 rocks. (e.g. 1986 - 241).
-    // In that time height increases 2783 (e.g. 3178 - 395).
-
-    let cycle_length = 1745u64;
-    let height_increase = 2783u64;
-
-    let excess = 1000000000000u64 % cycle_length;
-    let multiple_height = 1000000000000u64 / cycle_length;
-
-    let excess_height = simulate(excess, &wind, false);
-    println!(
-        "Height: {}",
-        multiple_height * height_increase + excess_height as u64
-    );
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Vec<char>;
+    type Answer1 = usize;
+    type Answer2 = u64;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day17.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        let mut jets = vec![];
+        for line in numbered_lines(input)? {
+            for (idx, c) in line.text.chars().enumerate() {
+                if c != '<' && c != '>' {
+                    return Err(line.char_error(idx, "jet must be '<' or '>'"));
+                }
+                jets.push(c);
+            }
+        }
+        Ok(jets)
+    }
+
+    fn part1(input: &Self::Input) -> Result<usize> {
+        Ok(simulate(2022, input))
+    }
+
+    fn part2(input: &Self::Input) -> Result<u64> {
+        // Far too many rocks to simulate, but the pile soon starts repeating
+        let mut chamber = Chamber::new();
+        let mut detector = CycleDetector::new();
+        let cycle = loop {
+            if let Some(cycle) = detector.record(chamber.key(), chamber.height() as i64) {
+                break cycle;
+            }
+            chamber.drop_rock(input);
+        };
+        Ok(cycle.extrapolate(1000000000000) as u64)
+    }
+
+    // `size` jets
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let jets: String = (0..size.max(1))
+            .map(|_| if rng.chance(50) { '<' } else { '>' })
+            .collect();
+        Generated::new(jets + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day17::part1(&example_input::<Day17>()).unwrap(), 3068);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(
+            Day17::part2(&example_input::<Day17>()).unwrap(),
+            1514285714288
+        );
+    }
+
+    #[test]
+    fn surface_skips_sealed_rows() {
+        let chamber = |rows: Vec<u8>| Chamber {
+            rows,
+            wind_idx: 0,
+            rock_type: 0,
+        };
+        // Different below a full row, and under an overhang
+        let a = chamber(vec![0b0000001, 0b1111111, 0b0011000, 0b1111110]);
+        let b = chamber(vec![0b1000000, 0b1111111, 0b0001000, 0b1111110]);
+        assert_eq!(a.surface(), b.surface());
+        assert_eq!(a.surface(), [0b1111111, 0b0000001, 0b0000111]);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day17>(17);
+        assert_eq!(Day17::part1(&input).unwrap(), 3239);
+        assert_eq!(Day17::part2(&input).unwrap(), 1594842406882);
+    }
 }
@@ -1,13 +1,12 @@
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::collections::HashSet;
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::{Param, Params},
+    solution::Solution,
+};
 
 pub fn neighbours(point: u32) -> Vec<u32> {
     let mut result = vec![];
@@ -15,7 +14,7 @@ pub fn neighbours(point: u32) -> Vec<u32> {
     let y = (point & 0x00ff00) >> 8;
     let z = point & 0xff;
     if z == 0 {
-        println!("{},{},{}", x, y, z);
+        crate::trace!("Neighbours of {},{},{} on the z boundary", x, y, z);
     }
     result.push(((x + 1) << 16) + (y << 8) + z);
     result.push(((x - 1) << 16) + (y << 8) + z);
@@ -26,19 +25,24 @@ pub fn neighbours(point: u32) -> Vec<u32> {
     result
 }
 
-pub fn reached_exit(point: u32) -> bool {
-    // Inspection of the data shows a minimum coord of 0 in each
-    // dimension and max of 19; however we deliberately add 1
-    // to avoid potential underflow. Boundary is therefore 0..21.
+pub fn reached_exit(point: u32, boundary: u32) -> bool {
+    // Coordinates run from 0 to the max_coord parameter; however we
+    // deliberately add 1 to avoid potential underflow. Boundary is
+    // therefore 0..max_coord + 2.
     let x = point >> 16;
     let y = (point & 0x00ff00) >> 8;
     let z = point & 0xff;
-    x == 0 || y == 0 || z == 0 || x == 21 || y == 21 || z == 21
+    x == 0 || y == 0 || z == 0 || x == boundary || y == boundary || z == boundary
 }
 
-pub fn exterior(point: u32, volume: &HashSet<u32>, tried: &mut HashSet<u32>) -> bool {
+pub fn exterior(
+    point: u32,
+    boundary: u32,
+    volume: &HashSet<u32>,
+    tried: &mut HashSet<u32>,
+) -> bool {
     // Recursive flood-fill algorithm; see if we can get out
-    if reached_exit(point) {
+    if reached_exit(point, boundary) {
         return true;
     }
     tried.insert(point);
@@ -47,60 +51,152 @@ pub fn exterior(point: u32, volume: &HashSet<u32>, tried: &mut HashSet<u32>) ->
         if volume.contains(&n) || tried.contains(&n) {
             continue;
         }
-        if exterior(n, volume, tried) {
+        if exterior(n, boundary, volume, tried) {
             return true;
         }
     }
     false
 }
 
-fn is_exterior(point: u32, volume: &HashSet<u32>) -> bool {
+fn is_exterior(point: u32, droplet: &Droplet) -> bool {
     let mut tried: HashSet<u32> = HashSet::new();
-    exterior(point, volume, &mut tried)
+    exterior(point, droplet.boundary, &droplet.cubes, &mut tried)
 }
 
-fn generate_data() -> HashSet<u32> {
+pub struct Droplet {
+    cubes: HashSet<u32>,
+    // Shifted coordinate of the faces of the box around the droplet
+    boundary: u32,
+}
+
+fn generate_data(input: &str, max_coord: u32) -> Result<HashSet<u32>> {
     let mut volume: HashSet<u32> = HashSet::new();
-    for line in read_list("inputs/day18.txt") {
-        let mut parts = line.split(',');
+    for line in numbered_lines(input)? {
+        let parts: Vec<&str> = line.text.split(',').collect();
+        if parts.len() != 3 {
+            return Err(line.error("expected a cube position such as '2,2,2'"));
+        }
         // Add one to each coordinate, shifting the whole thing without
         // changing it's surface area. This allows use of u32 arithmetic
         // without having to deal with underflow.
-        let x: u32 = parts.next().unwrap().parse::<u32>().unwrap() + 1;
-        let y: u32 = parts.next().unwrap().parse::<u32>().unwrap() + 1;
-        let z: u32 = parts.next().unwrap().parse::<u32>().unwrap() + 1;
+        let mut coords = [0; 3];
+        for (coord, part) in coords.iter_mut().zip(&parts) {
+            let value: u32 = line.parse(part)?;
+            // Must stay inside the boundary used by reached_exit()
+            if value > max_coord {
+                return Err(
+                    line.field_error(part, format!("coordinate must be from 0 to {}", max_coord))
+                );
+            }
+            *coord = value + 1;
+        }
+        let [x, y, z] = coords;
 
         // Urgh, I always forget that << has lower precedence than +.
         let point = (x << 16) + (y << 8) + z;
         volume.insert(point);
     }
-    volume
+    Ok(volume)
 }
 
-pub fn step1() {
-    let volume = generate_data();
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Droplet;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day18.txt");
+    const PARAMS: &'static [Param] = &[Param {
+        name: "max_coord",
+        default: "19",
+        example: None,
+    }];
+
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        let max_coord: u32 = params.get("max_coord")?;
+        // Coordinates are packed into a byte each, with room either side
+        if max_coord > 253 {
+            return Err(Error::new("max_coord must be at most 253"));
+        }
+        Ok(Droplet {
+            cubes: generate_data(input, max_coord)?,
+            boundary: max_coord + 2,
+        })
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        let volume = &input.cubes;
 
-    let mut surface_area = 0;
-    for cell in &volume {
-        for n in neighbours(*cell) {
-            if !volume.contains(&n) {
-                surface_area += 1;
+        let mut surface_area = 0;
+        for cell in volume {
+            for n in neighbours(*cell) {
+                if !volume.contains(&n) {
+                    surface_area += 1;
+                }
             }
         }
+        Ok(surface_area)
     }
-    println!("Surface area: {}", surface_area);
-}
 
-pub fn step2() {
-    let volume = generate_data();
+    fn part2(input: &Self::Input) -> Result<i32> {
+        let volume = &input.cubes;
 
-    let mut surface_area = 0;
-    for cell in &volume {
-        for n in neighbours(*cell) {
-            if !volume.contains(&n) && is_exterior(n, &volume) {
-                surface_area += 1;
+        let mut surface_area = 0;
+        for cell in volume {
+            for n in neighbours(*cell) {
+                if !volume.contains(&n) && is_exterior(n, input) {
+                    surface_area += 1;
+                }
             }
         }
+        Ok(surface_area)
+    }
+
+    // A lumpy droplet of `size` cubes, grown from the middle of a box
+    // roomy enough for it to enclose pockets of air
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let side = ((size as f64).cbrt() * 1.5).ceil().clamp(3.0, 254.0) as i64;
+        let count = size.clamp(1, (side * side * side) as usize);
+        let mut cubes = vec![[side / 2; 3]];
+        let mut seen = HashSet::from([cubes[0]]);
+        while cubes.len() < count {
+            let mut cube = *rng.choose(&cubes);
+            let axis = rng.below(3);
+            cube[axis] += if rng.chance(50) { 1 } else { -1 };
+            if (0..side).contains(&cube[axis]) && seen.insert(cube) {
+                cubes.push(cube);
+            }
+        }
+        rng.shuffle(&mut cubes);
+        let mut input = String::new();
+        for [x, y, z] in cubes {
+            input += &format!("{},{},{}\n", x, y, z);
+        }
+        Generated::new(input).with_param("max_coord", side - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day18::part1(&example_input::<Day18>()).unwrap(), 64);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day18::part2(&example_input::<Day18>()).unwrap(), 58);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day18>(18);
+        assert_eq!(Day18::part1(&input).unwrap(), 3466);
+        assert_eq!(Day18::part2(&input).unwrap(), 2012);
     }
-    println!("Exterior surface area: {}", surface_area);
 }
@@ -1,13 +1,12 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::collections::HashMap;
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    input::{numbered_lines, Line},
+    params::Params,
+    solution::{Reference, Solution},
+};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 struct State {
@@ -36,8 +35,8 @@ impl State {
     }
 }
 
-#[derive(Debug)]
-struct Blueprint {
+#[derive(Debug, Clone)]
+pub struct Blueprint {
     ore_robot_ore: i32,
     clay_robot_ore: i32,
     obsidian_robot_ore: i32,
@@ -49,22 +48,27 @@ struct Blueprint {
 }
 
 impl Blueprint {
-    fn new(line: &str) -> Self {
-        let mut parts = line.split_whitespace();
-
-        Self {
-            ore_robot_ore: parts.nth(6).unwrap().parse().unwrap(),
-            clay_robot_ore: parts.nth(5).unwrap().parse().unwrap(),
-            obsidian_robot_ore: parts.nth(5).unwrap().parse().unwrap(),
-            obsidian_robot_clay: parts.nth(2).unwrap().parse().unwrap(),
-            geode_robot_ore: parts.nth(5).unwrap().parse().unwrap(),
-            geode_robot_obsidian: parts.nth(2).unwrap().parse().unwrap(),
-            cache: HashMap::new(),
+    fn new(line: &Line) -> Result<Self> {
+        let parts: Vec<&str> = line.text.split_whitespace().collect();
+        if parts.len() != 32 || parts[0] != "Blueprint" {
+            return Err(line.error(
+                "expected 'Blueprint <id>: Each ore robot costs <n> ore. ...' on one line",
+            ));
         }
+
+        Ok(Self {
+            ore_robot_ore: line.parse(parts[6])?,
+            clay_robot_ore: line.parse(parts[12])?,
+            obsidian_robot_ore: line.parse(parts[18])?,
+            obsidian_robot_clay: line.parse(parts[21])?,
+            geode_robot_ore: line.parse(parts[27])?,
+            geode_robot_obsidian: line.parse(parts[30])?,
+            cache: HashMap::new(),
+        })
     }
 
     fn recurse(&mut self, state: State, remain: i32) -> i32 {
-        //println!("Remain: {}. State: {:?}", remain, state);
+        crate::trace!("Remain: {}. State: {:?}", remain, state);
         if remain == 0 {
             return state.geodes;
         }
@@ -84,7 +88,7 @@ impl Blueprint {
 
         // Second optimisation: we never need more robots (i.e. new resources)
         // than enough to build a (any) new robot each minute.
-        let max_robot_ore = *vec![
+        let max_robot_ore = *[
             self.ore_robot_ore,
             self.clay_robot_ore,
             self.obsidian_robot_ore,
@@ -156,43 +160,173 @@ impl Blueprint {
         best_option
     }
 
-    fn geode_count(&mut self, minutes: i32) -> i32 {
-        let state = State::new();
-        self.recurse(state, minutes)
+    /// Most geodes which can be opened in the given time.
+    pub fn geode_count(&self, minutes: i32) -> i32 {
+        let mut best = 0;
+        self.plan(State::new(), minutes, &mut best);
+        best
+    }
+
+    // Try building each kind of robot next, waiting until it's affordable,
+    // rather than going minute by minute like recurse(). As there, there's
+    // no point having more robots of a kind than can be spent each minute,
+    // and plans are abandoned once they couldn't beat the best so far even
+    // with a new geode robot every minute.
+    fn plan(&self, state: State, remain: i32, best: &mut i32) {
+        let geodes = state.geodes + state.geode_robots * remain;
+        *best = (*best).max(geodes);
+        if geodes + remain * (remain - 1) / 2 <= *best {
+            return;
+        }
+        // Ore, clay and obsidian cost of each kind of robot
+        let costs = [
+            (self.ore_robot_ore, 0, 0),
+            (self.clay_robot_ore, 0, 0),
+            (self.obsidian_robot_ore, self.obsidian_robot_clay, 0),
+            (self.geode_robot_ore, 0, self.geode_robot_obsidian),
+        ];
+        let max_robot_ore = costs.iter().map(|c| c.0).max().unwrap();
+        let enough = [
+            state.ore_robots >= max_robot_ore,
+            state.clay_robots >= self.obsidian_robot_clay,
+            state.obsidian_robots >= self.geode_robot_obsidian,
+            false,
+        ];
+        // Geode robots first, as they're most likely to raise the best
+        for (kind, (ore, clay, obsidian)) in costs.into_iter().enumerate().rev() {
+            if enough[kind] {
+                continue;
+            }
+            // Minutes collecting until there's enough of each resource
+            let mut wait = 0;
+            for (cost, have, robots) in [
+                (ore, state.ore, state.ore_robots),
+                (clay, state.clay, state.clay_robots),
+                (obsidian, state.obsidian, state.obsidian_robots),
+            ] {
+                if have >= cost {
+                    continue;
+                }
+                if robots == 0 {
+                    wait = remain;
+                    break;
+                }
+                wait = wait.max((cost - have + robots - 1) / robots);
+            }
+            // Then a minute to build it, with time left for it to collect
+            let minutes = wait + 1;
+            if minutes >= remain {
+                continue;
+            }
+            let mut next_state = state;
+            next_state.ore += state.ore_robots * minutes - ore;
+            next_state.clay += state.clay_robots * minutes - clay;
+            next_state.obsidian += state.obsidian_robots * minutes - obsidian;
+            next_state.geodes += state.geode_robots * minutes;
+            match kind {
+                0 => next_state.ore_robots += 1,
+                1 => next_state.clay_robots += 1,
+                2 => next_state.obsidian_robots += 1,
+                _ => next_state.geode_robots += 1,
+            }
+            self.plan(next_state, remain - minutes, best);
+        }
+    }
+
+    // The same as geode_count(), by the exhaustive minute by minute search
+    // to check it against.
+    fn reference_geode_count(&self, minutes: i32) -> i32 {
+        self.clone().recurse(State::new(), minutes)
     }
 }
 
-pub fn step1() {
-    let mut blueprints = vec![];
-    for line in read_list("inputs/day19.txt") {
-        blueprints.push(Blueprint::new(&line));
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = Vec<Blueprint>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day19.txt");
+    // Part 2 is the same search for longer, and too slow to crosscheck
+    const REFERENCE1: Option<Reference<Self::Input, i32>> = Some(|input| {
+        let mut total = 0;
+        for (idx, b) in input.iter().enumerate() {
+            total += b.reference_geode_count(24) * (idx as i32 + 1);
+        }
+        total
+    });
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        let mut blueprints = vec![];
+        for line in numbered_lines(input)? {
+            blueprints.push(Blueprint::new(&line)?);
+        }
+        crate::trace!("Blueprints: {:?}", blueprints);
+        Ok(blueprints)
     }
 
-    //println!("Blueprints: {:?}", blueprints);
+    fn part1(input: &Self::Input) -> Result<i32> {
+        let mut total = 0;
+        for (idx, b) in input.iter().enumerate() {
+            let bp_idx = idx as i32 + 1; // 1-based
+            let geodes = b.geode_count(24);
+            crate::info!("Blueprint {}: max geode count: {}", bp_idx, geodes);
+            total += geodes * bp_idx;
+        }
+        Ok(total)
+    }
 
-    let mut total = 0;
-    for (idx, b) in blueprints.iter_mut().enumerate() {
-        let bp_idx = idx as i32 + 1; // 1-based
-        let geodes = b.geode_count(24);
-        println!("Blueprint {}: max geode count: {}", bp_idx, geodes);
-        total += geodes * bp_idx;
+    fn part2(input: &Self::Input) -> Result<i32> {
+        let mut product = 1;
+        for (idx, b) in input.iter().take(3).enumerate() {
+            let bp_idx = idx as i32 + 1; // 1-based
+            let geodes = b.geode_count(32);
+            crate::info!("Blueprint {}: max geode count: {}", bp_idx, geodes);
+            product *= geodes;
+        }
+        Ok(product)
+    }
+
+    // `size` blueprints, with costs in the same ranges as the real puzzle's
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut input = String::new();
+        for id in 1..=size.max(1) {
+            input += &format!(
+                "Blueprint {}: Each ore robot costs {} ore. Each clay robot costs {} ore. Each obsidian robot costs {} ore and {} clay. Each geode robot costs {} ore and {} obsidian.\n",
+                id,
+                rng.range(2..=4),
+                rng.range(2..=4),
+                rng.range(2..=4),
+                rng.range(5..=20),
+                rng.range(2..=4),
+                rng.range(5..=20)
+            );
+        }
+        Generated::new(input)
     }
-    println!("Total quality level: {}", total);
 }
 
-pub fn step2() {
-    let mut blueprints = vec![];
-    for line in read_list("inputs/day19.txt").iter().take(3) {
-        blueprints.push(Blueprint::new(line));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day19::part1(&example_input::<Day19>()).unwrap(), 33);
     }
-    //println!("Blueprints: {:?}", blueprints);
-
-    let mut product = 1;
-    for (idx, b) in blueprints.iter_mut().enumerate() {
-        let bp_idx = idx as i32 + 1; // 1-based
-        let geodes = b.geode_count(32);
-        println!("Blueprint {}: max geode count: {}", bp_idx, geodes);
-        product *= geodes;
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day19::part2(&example_input::<Day19>()).unwrap(), 56 * 62);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day19>(19);
+        assert_eq!(Day19::part1(&input).unwrap(), 1127);
+        assert_eq!(Day19::part2(&input).unwrap(), 21546);
     }
-    println!("Geode product: {}", product);
 }
@@ -1,13 +1,11 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::read_ints,
+    params::Params,
+    solution::{Reference, Solution},
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
 #[derive(Debug)]
 struct Node {
     value: i64,
@@ -15,7 +13,8 @@ struct Node {
     prev_pos: usize,
 }
 
-fn decode(ciphertext: &[i64], mix_count: i32) -> Vec<i64> {
+/// Mix the numbers `mix_count` times.
+pub fn decode(ciphertext: &[i64], mix_count: i32) -> Vec<i64> {
     // Array-based circular linked list
     let mut ll: Vec<Node> = vec![];
     for element in 0..ciphertext.len() {
@@ -80,41 +79,103 @@ fn decode(ciphertext: &[i64], mix_count: i32) -> Vec<i64> {
     result
 }
 
-pub fn step1() {
-    let ciphertext: Vec<i64> = read_list("inputs/day20.txt")
-        .iter()
-        .map(|x| x.parse().unwrap())
-        .collect();
-
-    // Numbers are in approx range -10000..10000, and have repeats.
-    let cleartext = decode(&ciphertext, 1);
+// Mixing by taking each number out of a list and putting it back the
+// right number of places along, to check decode() against.
+fn reference_decode(ciphertext: &[i64], mix_count: i32) -> Vec<i64> {
+    // Indices into the ciphertext, in mixed order
+    let mut mixed: Vec<usize> = (0..ciphertext.len()).collect();
+    for _ in 0..mix_count {
+        for (idx, value) in ciphertext.iter().enumerate() {
+            let from = mixed.iter().position(|&m| m == idx).unwrap();
+            mixed.remove(from);
+            let to = (from as i64 + value).rem_euclid(mixed.len() as i64);
+            mixed.insert(to as usize, idx);
+        }
+    }
+    mixed.iter().map(|&idx| ciphertext[idx]).collect()
+}
 
+pub fn grove_coordinates(cleartext: &[i64]) -> i64 {
+    // Sum of 1000th, 2000th & 3000th after zero
     let zero_idx = cleartext.iter().position(|&x| x == 0).unwrap();
     let zero_1000 = (zero_idx + 1000) % cleartext.len();
     let zero_2000 = (zero_idx + 2000) % cleartext.len();
     let zero_3000 = (zero_idx + 3000) % cleartext.len();
 
-    println!(
-        "Sum of 1000th, 2000th & 3000th after zero: {}",
-        cleartext[zero_1000] + cleartext[zero_2000] + cleartext[zero_3000]
-    );
+    cleartext[zero_1000] + cleartext[zero_2000] + cleartext[zero_3000]
 }
 
-pub fn step2() {
-    let ciphertext: Vec<i64> = read_list("inputs/day20.txt")
-        .iter()
-        .map(|x| x.parse::<i64>().unwrap() * 811589153i64)
-        .collect();
+pub struct Day20;
+
+impl Solution for Day20 {
+    type Input = Vec<i64>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day20.txt");
+    const REFERENCE1: Option<Reference<Self::Input, i64>> =
+        Some(|input| grove_coordinates(&reference_decode(input, 1)));
+    const REFERENCE2: Option<Reference<Self::Input, i64>> = Some(|input| {
+        let ciphertext: Vec<i64> = input.iter().map(|x| x * 811589153i64).collect();
+        grove_coordinates(&reference_decode(&ciphertext, 10))
+    });
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        let numbers: Vec<i64> = read_ints(input)?;
+        // Moves are counted around the other numbers, so there must be some
+        if numbers.len() < 2 {
+            return Err(Error::new("the file must contain at least two numbers"));
+        }
+        // Grove coordinates are counted from the zero
+        if !numbers.contains(&0) {
+            return Err(Error::new("the file must contain a 0"));
+        }
+        Ok(numbers)
+    }
 
-    let cleartext = decode(&ciphertext, 10);
+    fn part1(input: &Self::Input) -> Result<i64> {
+        // Numbers are in approx range -10000..10000, and have repeats.
+        let cleartext = decode(input, 1);
+        Ok(grove_coordinates(&cleartext))
+    }
 
-    let zero_idx = cleartext.iter().position(|&x| x == 0).unwrap();
-    let zero_1000 = (zero_idx + 1000) % cleartext.len();
-    let zero_2000 = (zero_idx + 2000) % cleartext.len();
-    let zero_3000 = (zero_idx + 3000) % cleartext.len();
+    fn part2(input: &Self::Input) -> Result<i64> {
+        let ciphertext: Vec<i64> = input.iter().map(|x| x * 811589153i64).collect();
+        let cleartext = decode(&ciphertext, 10);
+        Ok(grove_coordinates(&cleartext))
+    }
+
+    // `size` numbers, at least two, with exactly one zero
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut numbers: Vec<i64> = (1..size.max(2))
+            .map(|_| rng.range(1..=10000) * if rng.chance(50) { 1 } else { -1 })
+            .collect();
+        numbers.insert(rng.below(numbers.len() + 1), 0);
+        let lines: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+        Generated::new(lines.join("\n") + "\n")
+    }
+}
 
-    println!(
-        "Sum of 1000th, 2000th & 3000th after zero: {}",
-        cleartext[zero_1000] + cleartext[zero_2000] + cleartext[zero_3000]
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day20::part1(&example_input::<Day20>()).unwrap(), 3);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day20::part2(&example_input::<Day20>()).unwrap(), 1623178306);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day20>(20);
+        assert_eq!(Day20::part1(&input).unwrap(), 23321);
+        assert_eq!(Day20::part2(&input).unwrap(), 1428396909280);
+    }
 }
@@ -1,21 +1,21 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::collections::{HashMap, HashSet};
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::Params,
+    solution::Solution,
+};
 
-#[derive(Debug)]
-enum Monkey {
+#[derive(Debug, Clone)]
+pub enum Monkey {
     Fact(f64),
     Computation(String, String, String),
 }
 
-fn evaluate(root: String, monkeys: &HashMap<String, Monkey>, human: Option<f64>) -> f64 {
+/// Value yelled by `root`, with `human` overriding the 'humn' monkey.
+pub fn evaluate(root: String, monkeys: &HashMap<String, Monkey>, human: Option<f64>) -> f64 {
     if let Some(humn) = human {
         if root.as_str() == "humn" {
             return humn;
@@ -41,7 +41,8 @@ fn evaluate(root: String, monkeys: &HashMap<String, Monkey>, human: Option<f64>)
     }
 }
 
-fn solve_for_human(monkeys: &HashMap<String, Monkey>) -> i64 {
+/// Value for 'humn' which makes the root monkey's equality check pass.
+pub fn solve_for_human(monkeys: &HashMap<String, Monkey>) -> Result<i64> {
     // Arbitrary big 2^n number. Needs to be big to maximise O(logN)
     // portion vs O(n) portion, and 2^n so can repeatedly half and
     // end up at 1.
@@ -51,9 +52,13 @@ fn solve_for_human(monkeys: &HashMap<String, Monkey>) -> i64 {
     let mut last_result: f64 = 0.;
     loop {
         let result = evaluate("root".to_string(), monkeys, Some(check));
-        // println!("Checking {}, result = {}", check, result);
+        crate::trace!("Checking {}, result = {}", check, result);
         if result == 0. {
-            return check as i64;
+            return Ok(check as i64);
+        }
+        // Overshot every whole number, or ran past where they're exact
+        if mult < 1. || check.abs() > (1u64 << 53) as f64 {
+            return Err(Error::new("no number for 'humn' passes root's check"));
         }
         if last_result != 0. && result.abs() > last_result.abs() {
             sign *= -1.;
@@ -64,50 +69,256 @@ fn solve_for_human(monkeys: &HashMap<String, Monkey>) -> i64 {
     }
 }
 
-pub fn step1() {
+// A monkey which ends up waiting on its own answer, following the jobs
+// from `name`. `waiting` holds those further up the chain, and `done` those
+// known to be clear.
+fn waits_on_itself<'a>(
+    name: &'a str,
+    monkeys: &'a HashMap<String, Monkey>,
+    waiting: &mut HashSet<&'a str>,
+    done: &mut HashSet<&'a str>,
+) -> Option<&'a str> {
+    if done.contains(name) {
+        return None;
+    }
+    if !waiting.insert(name) {
+        return Some(name);
+    }
+    if let Monkey::Computation(m1, _, m2) = &monkeys[name] {
+        for operand in [m1, m2] {
+            if let Some(monkey) = waits_on_itself(operand, monkeys, waiting, done) {
+                return Some(monkey);
+            }
+        }
+    }
+    waiting.remove(name);
+    done.insert(name);
+    None
+}
+
+fn read_monkeys(input: &str) -> Result<HashMap<String, Monkey>> {
     let mut monkeys = HashMap::new();
-    for rule in read_list("inputs/day21.txt") {
-        let mut parts = rule.split_whitespace();
-        let rule_id = parts.next().unwrap().trim_end_matches(':').to_string();
-        let p1 = parts.next().unwrap();
-        let mr: Monkey = match p1.parse::<f64>() {
-            Ok(value) => Monkey::Fact(value),
-            Err(_) => Monkey::Computation(
-                p1.to_string(),
-                parts.next().unwrap().to_string(),
-                parts.next().unwrap().to_string(),
-            ),
+    let lines = numbered_lines(input)?;
+    for rule in &lines {
+        // "root: pppw + sjmn" or "dbpl: 5"
+        let parts: Vec<&str> = rule.text.split_whitespace().collect();
+        let rule_id = match parts.first().and_then(|p| p.strip_suffix(':')) {
+            Some(id) => id.to_string(),
+            None => return Err(rule.error("expected '<name>: <job>'")),
         };
-        monkeys.insert(rule_id, mr);
+        let mr: Monkey = match parts[1..] {
+            [value] => Monkey::Fact(rule.parse(value)?),
+            [m1, op @ ("+" | "-" | "*" | "/"), m2] => {
+                Monkey::Computation(m1.to_string(), op.to_string(), m2.to_string())
+            }
+            [_, op, _] => return Err(rule.field_error(op, "operation must be +, -, * or /")),
+            _ => return Err(rule.error("expected a number or '<name> <op> <name>'")),
+        };
+        if monkeys.insert(rule_id, mr).is_some() {
+            return Err(rule.char_error(0, "duplicate monkey"));
+        }
+    }
+    // Every monkey must be able to get its operands
+    for rule in &lines {
+        if let [_, m1, _, m2] = rule.text.split_whitespace().collect::<Vec<_>>()[..] {
+            for name in [m1, m2] {
+                if !monkeys.contains_key(name) {
+                    return Err(rule.field_error(name, "unknown monkey"));
+                }
+            }
+        }
+    }
+    // Evaluating would never finish otherwise
+    let names: Vec<&str> = lines
+        .iter()
+        .map(|rule| rule.text.split(':').next().unwrap_or_default().trim())
+        .collect();
+    let mut done = HashSet::new();
+    for name in &names {
+        if let Some(monkey) = waits_on_itself(name, &monkeys, &mut HashSet::new(), &mut done) {
+            let rule = &lines[names.iter().position(|&n| n == monkey).unwrap()];
+            return Err(rule.error("monkey waits on its own answer"));
+        }
+    }
+    for name in ["root", "humn"] {
+        if !monkeys.contains_key(name) {
+            return Err(Error::new(format!("no '{}' monkey", name)));
+        }
+    }
+    if !matches!(monkeys["root"], Monkey::Computation(..)) {
+        return Err(Error::new("'root' must be an operation"));
     }
+    Ok(monkeys)
+}
 
-    let result = evaluate("root".to_string(), &monkeys, None);
-    println!("Root: {}", result);
+// Monkeys being generated, with their jobs.
+struct Troop {
+    names: HashSet<String>,
+    jobs: Vec<String>,
 }
 
-pub fn step2() {
-    let mut monkeys = HashMap::new();
-    for rule in read_list("inputs/day21.txt") {
-        let mut parts = rule.split_whitespace();
-        let rule_id = parts.next().unwrap().trim_end_matches(':').to_string();
-        let is_root = rule_id == "root";
-        let p1 = parts.next().unwrap();
-        let mr: Monkey = match p1.parse::<f64>() {
-            Ok(value) => Monkey::Fact(value),
-            Err(_) => Monkey::Computation(
-                p1.to_string(),
-                if is_root {
-                    parts.next();
-                    "=".to_string()
-                } else {
-                    parts.next().unwrap().to_string()
-                },
-                parts.next().unwrap().to_string(),
-            ),
+impl Troop {
+    // Four letters, like the real puzzle's
+    fn name(&mut self, rng: &mut Rng) -> String {
+        loop {
+            let name: String = (0..4)
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect();
+            if name != "root" && name != "humn" && self.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    // A monkey yelling `value`, a positive number, worked out from around
+    // `count` monkeys whose divisions are all exact. Returns its name.
+    fn yell(&mut self, rng: &mut Rng, value: i64, count: usize) -> String {
+        let name = self.name(rng);
+        if count < 3 {
+            self.jobs.push(format!("{}: {}", name, value));
+            return name;
+        }
+        let divisors: Vec<i64> = (2..=9).filter(|d| value % d == 0).collect();
+        let (left, op, right) = match rng.below(4) {
+            0 if value >= 2 => {
+                let a = rng.range(1..=value - 1);
+                (a, '+', value - a)
+            }
+            1 if !divisors.is_empty() => {
+                let d = *rng.choose(&divisors);
+                (value / d, '*', d)
+            }
+            2 if value < 1000000000 => {
+                let d = rng.range(2..=9);
+                (value * d, '/', d)
+            }
+            _ => {
+                let b = rng.range(1..=100);
+                (value + b, '-', b)
+            }
+        };
+        let left_count = 1 + rng.below(count - 2);
+        let left = self.yell(rng, left, left_count);
+        let right = self.yell(rng, right, count - 1 - left_count);
+        self.jobs
+            .push(format!("{}: {} {} {}", name, left, op, right));
+        name
+    }
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    type Input = HashMap<String, Monkey>;
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day21.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        read_monkeys(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i64> {
+        Ok(evaluate("root".to_string(), input, None) as i64)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i64> {
+        let mut monkeys = input.clone();
+        // The root monkey's operation becomes an equality check
+        if let Some(Monkey::Computation(_, op, _)) = monkeys.get_mut("root") {
+            *op = "=".to_string();
+        }
+        solve_for_human(&monkeys)
+    }
+
+    // About `size` monkeys, with the human's number passing through a chain
+    // of them to one side of the root's comparison
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let mut troop = Troop {
+            names: HashSet::new(),
+            jobs: vec![],
         };
-        monkeys.insert(rule_id, mr);
+        let links = (size / 8).clamp(1, 40);
+        let count = size / (links + 1);
+        // What the human yells, and what they should yell for part 2
+        let yelled = rng.range(1..=1000);
+        let answer = rng.range(1..=1000000);
+        troop.jobs.push(format!("humn: {}", yelled));
+        let (mut name, mut at_yelled, mut at_answer) = ("humn".to_string(), yelled, answer);
+        let mut link = 0;
+        // The far side of the root's comparison is yelled as a positive
+        // number, so the chain must end up positive too
+        while link < links || at_answer < 1 {
+            let mut op = *rng.choose(&['+', '-', '*']);
+            // Kept small enough to be exact in floating point
+            if op == '*' && at_answer.abs().max(at_yelled.abs()) >= 1 << 30 {
+                op = '-';
+            }
+            if link >= links {
+                op = '+';
+            }
+            let value = match op {
+                '*' => rng.range(2..=5),
+                '+' if at_answer < 1 => 1 - at_answer + rng.range(0..=1000),
+                _ => rng.range(1..=1000),
+            };
+            let other = troop.yell(rng, value, count);
+            let human_first = rng.chance(50);
+            let apply = |at: i64| match (op, human_first) {
+                ('+', _) => at + value,
+                ('*', _) => at * value,
+                ('-', true) => at - value,
+                _ => value - at,
+            };
+            at_yelled = apply(at_yelled);
+            at_answer = apply(at_answer);
+            let next = troop.name(rng);
+            troop.jobs.push(match human_first {
+                true => format!("{}: {} {} {}", next, name, op, other),
+                false => format!("{}: {} {} {}", next, other, op, name),
+            });
+            name = next;
+            link += 1;
+        }
+        let other = troop.yell(rng, at_answer, count);
+        troop.jobs.push(format!("root: {} + {}", name, other));
+        rng.shuffle(&mut troop.jobs);
+        Generated::new(troop.jobs.join("\n") + "\n")
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
 
-    let result = solve_for_human(&monkeys);
-    println!("Human: {}", result);
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day21::part1(&example_input::<Day21>()).unwrap(), 152);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day21::part2(&example_input::<Day21>()).unwrap(), 301);
+    }
+
+    #[test]
+    fn unanswerable_monkeys() {
+        let params = Params::new(Day21::PARAMS, false);
+        let looped = "root: abcd + humn\nabcd: efgh * 2\nefgh: abcd - humn\nhumn: 5\n";
+        let e = Day21::parse(looped, &params).unwrap_err();
+        assert_eq!(e.line, Some(2));
+        // The human's number never reaches root
+        let unrelated = Day21::parse("root: abcd + efgh\nabcd: 1\nefgh: 2\nhumn: 5\n", &params);
+        assert!(Day21::part2(&unrelated.unwrap()).is_err());
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day21>(21);
+        assert_eq!(Day21::part1(&input).unwrap(), 331120084396440);
+        assert_eq!(Day21::part2(&input).unwrap(), 3378273370680);
+    }
 }
@@ -1,396 +1,285 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    geometry::{Heading, Point},
+    grid::Grid,
+    input::numbered_paragraphs,
+    params::{Param, Params},
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum CellType {
     Void,
     Wall,
     Empty,
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct State {
-    direction: Direction,
-    position: (i32, i32),
+    direction: Heading,
+    position: Point,
 }
 
 impl State {
-    fn turn_right(&mut self) {
-        match self.direction {
-            Direction::North => self.direction = Direction::East,
-            Direction::East => self.direction = Direction::South,
-            Direction::South => self.direction = Direction::West,
-            Direction::West => self.direction = Direction::North,
-        }
-    }
-    fn turn_left(&mut self) {
-        match self.direction {
-            Direction::North => self.direction = Direction::West,
-            Direction::East => self.direction = Direction::North,
-            Direction::South => self.direction = Direction::East,
-            Direction::West => self.direction = Direction::South,
-        }
-    }
-
     fn value(&self) -> i32 {
-        (self.position.0 + 1) * 1000
-            + (self.position.1 + 1) * 4
+        (self.position.y + 1) * 1000
+            + (self.position.x + 1) * 4
             + (match self.direction {
-                Direction::East => 0,
-                Direction::South => 1,
-                Direction::West => 2,
-                Direction::North => 3,
+                Heading::East => 0,
+                Heading::South => 1,
+                Heading::West => 2,
+                Heading::North => 3,
             })
     }
 }
 
-#[derive(Debug)]
-struct World {
-    grid: HashMap<(i32, i32), CellType>,
+#[derive(Debug, Clone)]
+pub struct World {
+    grid: Grid<CellType>,
     state: State,
     rules: Vec<char>,
     is_cube: bool,
+    // Length of each edge of the cube
+    face_size: i32,
+    // Folded only for part 2, as part 1 takes any map
+    faces: Vec<Face>,
 }
 
-impl World {
-    fn new(filename: &str, is_cube: bool) -> Self {
-        let mut grid: HashMap<(i32, i32), CellType> = HashMap::new();
-        let mut first_pos = None;
-        let lines = read_list(filename);
-        let mut rules: String = "".to_string();
-        for (row_idx, line) in lines.iter().enumerate() {
-            if line.is_empty() {
-                rules = lines[row_idx + 1].clone();
-                break;
+type Vector = [i32; 3];
+
+fn neg(v: Vector) -> Vector {
+    [-v[0], -v[1], -v[2]]
+}
+
+fn dot(a: Vector, b: Vector) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// One face of the cube, with its place in the map and which way it
+/// points once the map is folded up.
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    // Column and row of the face in the map, counted in faces
+    net: Point,
+    // Outwards from the cube, and along the face's columns and rows
+    normal: Vector,
+    right: Vector,
+    down: Vector,
+}
+
+impl Face {
+    fn direction(&self, heading: Heading) -> Vector {
+        match heading {
+            Heading::East => self.right,
+            Heading::West => neg(self.right),
+            Heading::South => self.down,
+            Heading::North => neg(self.down),
+        }
+    }
+
+    // The face folded over its edge in the given direction
+    fn fold(&self, heading: Heading) -> Self {
+        let (normal, right, down) = match heading {
+            Heading::East => (self.right, neg(self.normal), self.down),
+            Heading::West => (neg(self.right), self.normal, self.down),
+            Heading::South => (self.down, self.right, neg(self.normal)),
+            Heading::North => (neg(self.down), self.right, self.normal),
+        };
+        Self {
+            net: self.net.step(heading),
+            normal,
+            right,
+            down,
+        }
+    }
+}
+
+// The six faces of the map, folded up around the first. Maps which don't
+// fold into a cube with faces `face_size` tiles across are rejected.
+fn fold_cube(grid: &Grid<CellType>, face_size: i32) -> Result<Vec<Face>> {
+    let n = face_size as usize;
+    let mut nets = vec![];
+    for row in 0..grid.height().div_ceil(n) {
+        for col in 0..grid.width().div_ceil(n) {
+            let tiles = (0..n * n)
+                .filter_map(|idx| {
+                    grid.checked_pos((row * n + idx / n) as i64, (col * n + idx % n) as i64)
+                })
+                .filter(|&pos| grid.get(pos) != Some(&CellType::Void))
+                .count();
+            if tiles == n * n {
+                nets.push(Point::new(col as i32, row as i32));
+            } else if tiles > 0 {
+                return Err(Error::new(format!(
+                    "map doesn't divide into faces {} tiles across",
+                    face_size
+                )));
             }
-            let row_idx = row_idx as i32;
-            for (col_idx, c) in line.chars().enumerate() {
-                let col_idx = col_idx as i32;
-                match c {
-                    ' ' => {
-                        grid.insert((row_idx, col_idx), CellType::Void);
-                    }
-                    '.' => {
-                        if first_pos.is_none() {
-                            first_pos = Some((row_idx, col_idx));
-                        }
-                        grid.insert((row_idx, col_idx), CellType::Empty);
-                    }
-                    '#' => {
-                        grid.insert((row_idx, col_idx), CellType::Wall);
-                    }
-                    _ => panic!("Invalid branch"),
+        }
+    }
+    if nets.len() != 6 {
+        return Err(Error::new(format!(
+            "map has {} faces {} tiles across, not 6",
+            nets.len(),
+            face_size
+        )));
+    }
+
+    let mut faces = vec![Face {
+        net: nets[0],
+        normal: [0, 0, -1],
+        right: [1, 0, 0],
+        down: [0, 1, 0],
+    }];
+    let mut idx = 0;
+    while idx < faces.len() {
+        let face = faces[idx];
+        for heading in Heading::ALL {
+            let next = face.fold(heading);
+            if nets.contains(&next.net) && !faces.iter().any(|f| f.net == next.net) {
+                faces.push(next);
+            }
+        }
+        idx += 1;
+    }
+    let mut normals: Vec<Vector> = faces.iter().map(|f| f.normal).collect();
+    normals.sort();
+    normals.dedup();
+    if normals.len() != 6 {
+        return Err(Error::new("map doesn't fold into a cube"));
+    }
+    Ok(faces)
+}
+
+impl World {
+    fn new(input: &str, face_size: i32) -> Result<Self> {
+        let paragraphs = numbered_paragraphs(input)?;
+        // The map, then a blank line, then the path description
+        if paragraphs.len() != 2 {
+            return Err(Error::new(
+                "expected a map, then a blank line, then the path description",
+            ));
+        }
+        let mut rules = vec![];
+        for line in &paragraphs[1] {
+            for (idx, c) in line.text.chars().enumerate() {
+                if !matches!(c, 'L' | 'R' | '0'..='9') {
+                    return Err(line.char_error(idx, "path must be step counts, 'L' or 'R'"));
                 }
+                rules.push(c);
             }
         }
-        Self {
+        // Lines of the map are only as long as their last tile
+        let grid = Grid::from_lines(&paragraphs[0], CellType::Void, |c| match c {
+            ' ' => Some(CellType::Void),
+            '.' => Some(CellType::Empty),
+            '#' => Some(CellType::Wall),
+            _ => None,
+        })?;
+        let first_pos = grid.find(|c| c == &CellType::Empty).map(Point::from);
+        Ok(Self {
             grid,
             state: State {
-                direction: Direction::East,
-                position: first_pos.unwrap(),
+                direction: Heading::East,
+                position: first_pos.ok_or_else(|| Error::new("map has no open tiles"))?,
             },
-            rules: rules.chars().collect(),
-            is_cube,
-        }
+            rules,
+            is_cube: false,
+            face_size,
+            faces: vec![],
+        })
+    }
+
+    fn cell(&self, position: Point) -> Option<&CellType> {
+        let pos = self
+            .grid
+            .checked_pos(position.y as i64, position.x as i64)?;
+        self.grid.get(pos)
     }
 
     fn forward_wrap(&mut self, count: u32) {
-        let incr = match self.state.direction {
-            Direction::North => (-1, 0),
-            Direction::East => (0, 1),
-            Direction::South => (1, 0),
-            Direction::West => (0, -1),
-        };
+        let incr = self.state.direction.offset();
+        let (width, height) = (self.grid.width() as i32, self.grid.height() as i32);
         for _ in 0..count {
-            let mut target_pos = (
-                self.state.position.0 + incr.0,
-                self.state.position.1 + incr.1,
-            );
-            let mut target_cell = self.grid.get(&target_pos);
+            let mut target_pos = self.state.position + incr;
+            let mut target_cell = self.cell(target_pos);
             while target_cell.is_none() || target_cell.unwrap() == &CellType::Void {
-                if target_pos.0 < 0 {
-                    target_pos.0 = 200;
+                if target_pos.y < 0 {
+                    target_pos.y = height;
                 }
-                if target_pos.0 > 200 {
-                    target_pos.0 = -1;
+                if target_pos.y > height {
+                    target_pos.y = -1;
                 }
-                if target_pos.1 < 0 {
-                    target_pos.1 = 150;
+                if target_pos.x < 0 {
+                    target_pos.x = width;
                 }
-                if target_pos.1 > 150 {
-                    target_pos.1 = -1;
+                if target_pos.x > width {
+                    target_pos.x = -1;
                 }
 
-                target_pos.0 += incr.0;
-                target_pos.1 += incr.1;
-                target_cell = self.grid.get(&target_pos);
+                target_pos += incr;
+                target_cell = self.cell(target_pos);
             }
             if target_cell.unwrap() == &CellType::Empty {
                 self.state.position = target_pos;
             }
             // otherwise we hit a wall; do nothing.
         }
-        //println!("World state: {:?}", self.state);
+        crate::trace!("World state: {:?}", self.state);
     }
 
-    fn cube_face(&self, position: (i32, i32)) -> Option<char> {
-        // Cube layout (at least for my data):
-        //   AB
-        //   C
-        //  DE
-        //  F
-        if position.0 < 0 || position.0 > 199 || position.1 < 0 || position.1 > 149 {
+    fn cube_face(&self, position: Point) -> Option<&Face> {
+        if position.y < 0 || position.x < 0 {
             return None;
         }
-        if position.0 < 50 {
-            if position.1 < 50 {
-                None
-            } else if position.1 < 100 {
-                Some('A')
-            } else {
-                Some('B')
-            }
-        } else if position.0 < 100 {
-            if position.1 < 50 || position.1 > 99 {
-                return None;
-            };
-            Some('C')
-        } else if position.0 < 150 {
-            if position.1 > 99 {
-                return None;
-            };
-            if position.1 < 50 {
-                Some('D')
-            } else {
-                Some('E')
-            }
-        } else {
-            if position.1 > 49 {
-                return None;
-            };
-            Some('F')
-        }
+        let net = Point::new(position.x / self.face_size, position.y / self.face_size);
+        self.faces.iter().find(|f| f.net == net)
     }
 
-    fn same_face(&self, position: (i32, i32)) -> bool {
-        let my_face = self.cube_face(self.state.position);
-        assert!(my_face.is_some());
-        my_face == self.cube_face(position)
+    // Position and heading after stepping off the edge of a face, onto the
+    // face it's folded against
+    fn wrap_cube(&self, face: &Face) -> (Point, Heading) {
+        let n = self.face_size;
+        let local = self.state.position - face.net * n;
+        // Tiles' centres in 3D, in half tiles from the middle of the cube
+        let mut centre = [0; 3];
+        for (axis, c) in centre.iter_mut().enumerate() {
+            *c = n * face.normal[axis]
+                + (2 * local.x + 1 - n) * face.right[axis]
+                + (2 * local.y + 1 - n) * face.down[axis];
+        }
+        // Over the edge, the tile is a half tile further along the way
+        // we're heading and a half tile back into the cube
+        let out = face.direction(self.state.direction);
+        for (axis, c) in centre.iter_mut().enumerate() {
+            *c += out[axis] - face.normal[axis];
+        }
+        let next = self
+            .faces
+            .iter()
+            .find(|f| f.normal == out)
+            .expect("folded cube has every face");
+        let local = Point::new(
+            (dot(centre, next.right) + n - 1) / 2,
+            (dot(centre, next.down) + n - 1) / 2,
+        );
+        let heading = Heading::ALL
+            .into_iter()
+            .find(|&h| next.direction(h) == neg(face.normal))
+            .unwrap();
+        (next.net * n + local, heading)
     }
 
     fn forward_cube(&mut self, count: u32) {
-        // Cube layout (at least for my data):
-        //   AB
-        //   C
-        //  DE
-        //  F
-        //
-        // So interesting transitions are:
-        // A & West
-        // A & North
-        // B & East
-        // B & South
-        // B & North
-        // C & East
-        // C & West
-        // D & West
-        // D & North
-        // E & East
-        // E & South
-        // F & East
-        // F & South
-        // F & West
-        const A_TOP: i32 = 0;
-        const A_LEFT: i32 = 50;
-        const A_BOTTOM: i32 = 49;
-
-        const B_TOP: i32 = 0;
-        const B_LEFT: i32 = 100;
-        const B_BOTTOM: i32 = 49;
-        const B_RIGHT: i32 = 149;
-
-        const C_TOP: i32 = 50;
-        const C_LEFT: i32 = 50;
-        const C_RIGHT: i32 = 99;
-
-        const D_TOP: i32 = 100;
-        const D_LEFT: i32 = 0;
-        const D_BOTTOM: i32 = 149;
-
-        const E_TOP: i32 = 100;
-        const E_LEFT: i32 = 50;
-        const E_RIGHT: i32 = 99;
-        const E_BOTTOM: i32 = 149;
-
-        const F_TOP: i32 = 150;
-        const F_LEFT: i32 = 0;
-        const F_RIGHT: i32 = 49;
-        const F_BOTTOM: i32 = 199;
-
         for _ in 0..count {
-            let incr = match self.state.direction {
-                Direction::North => (-1, 0),
-                Direction::East => (0, 1),
-                Direction::South => (1, 0),
-                Direction::West => (0, -1),
-            };
-            let target_pos = (
-                self.state.position.0 + incr.0,
-                self.state.position.1 + incr.1,
-            );
-            // Defaults: nice transitions, such as A->B
-            let mut next_pos = target_pos;
+            let face = *self.cube_face(self.state.position).unwrap();
+            let mut next_pos = self.state.position.step(self.state.direction);
             let mut next_dir = self.state.direction;
-            if !self.same_face(target_pos) {
-                // At this point, we're leaving our current face in
-                // state.direction. Do cube wrap-around.
-                match self.cube_face(self.state.position).unwrap() {
-                    'A' => {
-                        let offset = (
-                            self.state.position.0 - A_TOP,
-                            self.state.position.1 - A_LEFT,
-                        );
-                        match self.state.direction {
-                            Direction::West => {
-                                // Moves to D
-                                next_dir = Direction::East;
-                                next_pos = (D_BOTTOM - offset.0, D_LEFT);
-                            }
-                            Direction::North => {
-                                // Moves to F
-                                next_dir = Direction::East;
-                                next_pos = (offset.1 + F_TOP, F_LEFT);
-                            }
-                            _ => {}
-                        }
-                    }
-                    //   AB
-                    //   C
-                    //  DE
-                    //  F
-                    'B' => {
-                        let offset = (
-                            self.state.position.0 - B_TOP,
-                            self.state.position.1 - B_LEFT,
-                        );
-                        match self.state.direction {
-                            Direction::East => {
-                                // Moves to E, inverted
-                                next_dir = Direction::West;
-                                next_pos = (E_BOTTOM - offset.0, E_RIGHT);
-                            }
-                            Direction::South => {
-                                // Moves to C
-                                next_dir = Direction::West;
-                                next_pos = (offset.1 + C_TOP, C_RIGHT);
-                            }
-                            Direction::North => {
-                                // Moves to F, direction unchanged
-                                next_pos = (F_BOTTOM, offset.1 + F_LEFT);
-                            }
-                            _ => {}
-                        }
-                    }
-                    'C' => {
-                        let offset = (
-                            self.state.position.0 - C_TOP,
-                            self.state.position.1 - C_LEFT,
-                        );
-                        match self.state.direction {
-                            Direction::East => {
-                                // Moves to B
-                                next_dir = Direction::North;
-                                next_pos = (B_BOTTOM, offset.0 + B_LEFT);
-                            }
-                            Direction::West => {
-                                // Moves to D
-                                next_dir = Direction::South;
-                                next_pos = (D_TOP, offset.0 + D_LEFT);
-                            }
-                            _ => {}
-                        }
-                    }
-                    'D' => {
-                        let offset = (
-                            self.state.position.0 - D_TOP,
-                            self.state.position.1 - D_LEFT,
-                        );
-                        match self.state.direction {
-                            Direction::West => {
-                                // Moves to A
-                                next_dir = Direction::East;
-                                next_pos = (A_BOTTOM - offset.0, A_LEFT);
-                            }
-                            Direction::North => {
-                                // Moves to C
-                                next_dir = Direction::East;
-                                next_pos = (offset.1 + C_TOP, C_LEFT);
-                            }
-                            _ => {}
-                        }
-                    }
-                    'E' => {
-                        let offset = (
-                            self.state.position.0 - E_TOP,
-                            self.state.position.1 - E_LEFT,
-                        );
-                        match self.state.direction {
-                            Direction::East => {
-                                // Moves to B
-                                next_dir = Direction::West;
-                                next_pos = (B_BOTTOM - offset.0, B_RIGHT);
-                            }
-                            Direction::South => {
-                                // Moves to F
-                                next_dir = Direction::West;
-                                next_pos = (offset.1 + F_TOP, F_RIGHT);
-                            }
-                            _ => {}
-                        }
-                    }
-                    'F' => {
-                        let offset = (
-                            self.state.position.0 - F_TOP,
-                            self.state.position.1 - F_LEFT,
-                        );
-                        match self.state.direction {
-                            Direction::East => {
-                                // Moves to E
-                                next_dir = Direction::North;
-                                next_pos = (E_BOTTOM, offset.0 + E_LEFT);
-                            }
-                            Direction::South => {
-                                // Moves to B, direction unchanged
-                                next_pos = (B_TOP, offset.1 + B_LEFT);
-                            }
-                            Direction::West => {
-                                // Moves to A
-                                next_dir = Direction::South;
-                                next_pos = (A_TOP, offset.0 + A_LEFT);
-                            }
-                            _ => {}
-                        }
-                    }
-                    _ => panic!("Unkown cube face"),
-                }
+            if self.cube_face(next_pos).is_none_or(|f| f.net != face.net) {
+                (next_pos, next_dir) = self.wrap_cube(&face);
             }
-            if self.grid.get(&next_pos) == Some(&CellType::Wall) {
+            if self.cell(next_pos) == Some(&CellType::Wall) {
                 // Blocked; no point trying to go forward any further
                 break;
             }
@@ -414,12 +303,12 @@ impl World {
                 'L' => {
                     self.forward(forward_count);
                     forward_count = 0;
-                    self.state.turn_left()
+                    self.state.direction = self.state.direction.turn_left()
                 }
                 'R' => {
                     self.forward(forward_count);
                     forward_count = 0;
-                    self.state.turn_right()
+                    self.state.direction = self.state.direction.turn_right()
                 }
                 '0'..='9' => {
                     forward_count *= 10;
@@ -432,16 +321,119 @@ impl World {
     }
 }
 
-pub fn step1() {
-    let mut world = World::new("inputs/day22.txt", false);
+pub struct Day22;
+
+impl Solution for Day22 {
+    type Input = World;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day22.txt");
+    const PARAMS: &'static [Param] = &[Param {
+        name: "face_size",
+        default: "50",
+        example: Some("4"),
+    }];
+
+    fn parse(input: &str, params: &Params) -> Result<Self::Input> {
+        let face_size: i32 = params.get("face_size")?;
+        if face_size <= 0 {
+            return Err(Error::new("face_size must be positive"));
+        }
+        World::new(input, face_size)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Flat world value
+        let mut world = input.clone();
+        world.is_cube = false;
 
-    world.traverse();
-    println!("Flat world value: {}", world.state.value());
+        world.traverse();
+        Ok(world.state.value())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        // Cube world value
+        let mut world = input.clone();
+        world.is_cube = true;
+        world.faces = fold_cube(&world.grid, world.face_size)?;
+
+        world.traverse();
+        Ok(world.state.value())
+    }
+
+    // A cube with faces `size` tiles across, folded out the same way as
+    // the real puzzle's, and a path of 40 moves per tile
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let n = size.max(2);
+        // Faces in each band of rows
+        const NET: [&str; 4] = [" AB", " C", "DE", "F"];
+        let mut input = String::new();
+        for row in 0..4 * n {
+            for face in NET[row / n].chars() {
+                for _ in 0..n {
+                    input.push(match face {
+                        ' ' => ' ',
+                        _ if rng.chance(10) => '#',
+                        _ => '.',
+                    });
+                }
+            }
+            input.push('\n');
+        }
+        input.push('\n');
+        input += &rng.range(1..=2 * n as i64).to_string();
+        for _ in 0..40 * n {
+            input.push(*rng.choose(&['L', 'R']));
+            input += &rng.range(1..=2 * n as i64).to_string();
+        }
+        Generated::new(input + "\n").with_param("face_size", n)
+    }
 }
 
-pub fn step2() {
-    let mut world = World::new("inputs/day22.txt", true);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
 
-    world.traverse();
-    println!("Cube world value: {}", world.state.value());
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day22::part1(&example_input::<Day22>()).unwrap(), 6032);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day22::part2(&example_input::<Day22>()).unwrap(), 5031);
+    }
+
+    #[test]
+    fn maps_must_fold() {
+        let mut params = Params::new(Day22::PARAMS, true);
+        params.set("face_size", "3").unwrap();
+        let world = Day22::parse(Day22::EXAMPLE, &params).unwrap();
+        assert!(Day22::part2(&world).is_err());
+        // Six faces, but in a row
+        let params = Params::new(Day22::PARAMS, true);
+        let row = format!("{}\n", ".".repeat(24)).repeat(4);
+        let world = Day22::parse(&(row + "\n4\n"), &params).unwrap();
+        assert!(Day22::part2(&world).is_err());
+    }
+
+    #[test]
+    fn flat_map() {
+        // Not a cube net, but part 1 only wraps around the map
+        let params = Params::new(Day22::PARAMS, false);
+        let world = Day22::parse(" ..#\n...\n\nR3L2\n", &params).unwrap();
+        // Down through the bottom back to the top, then across the right
+        // edge to the start of the second row
+        assert_eq!(Day22::part1(&world).unwrap(), 2000 + 4);
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day22>(22);
+        assert_eq!(Day22::part1(&input).unwrap(), 76332);
+        assert_eq!(Day22::part2(&input).unwrap(), 144012);
+    }
 }
@@ -1,119 +1,97 @@
-use std::{
-    collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
+use std::collections::HashMap;
+
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    geometry::{Heading, Point},
+    grid::Grid,
+    params::Params,
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
+// Directions are considered in this order, starting one later each round
+const ORDER: [Heading; 4] = [Heading::North, Heading::South, Heading::West, Heading::East];
 
 #[derive(Debug, Clone, Copy)]
 struct Elf {
-    position: (i32, i32),
-    // Direction: 0=N, 1=S, 2=W, 3=E
-    next_dir: i32,
+    position: Point,
+    // Index into ORDER
+    next_dir: usize,
 }
 
 impl Elf {
-    fn new(position: (i32, i32)) -> Self {
+    fn new(position: Point) -> Self {
         Elf {
             position,
             next_dir: 0,
         }
     }
 
-    fn alone(&self, grove: &HashMap<(i32, i32), Elf>) -> bool {
-        !(grove.contains_key(&(self.position.0 - 1, self.position.1 - 1))
-            || grove.contains_key(&(self.position.0 - 1, self.position.1))
-            || grove.contains_key(&(self.position.0 - 1, self.position.1 + 1))
-            || grove.contains_key(&(self.position.0, self.position.1 - 1))
-            || grove.contains_key(&(self.position.0, self.position.1 + 1))
-            || grove.contains_key(&(self.position.0 + 1, self.position.1 - 1))
-            || grove.contains_key(&(self.position.0 + 1, self.position.1))
-            || grove.contains_key(&(self.position.0 + 1, self.position.1 + 1)))
-    }
-
-    fn move_pos(&self, dir: i32) -> (i32, i32) {
-        match dir {
-            0 => (self.position.0 - 1, self.position.1), // North
-            1 => (self.position.0 + 1, self.position.1), // South
-            2 => (self.position.0, self.position.1 - 1), // West
-            3 => (self.position.0, self.position.1 + 1), // East
-            _ => panic!("Invalid direction"),
+    fn alone(&self, grove: &HashMap<Point, Elf>) -> bool {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let neighbour = self.position + Point::new(dx, dy);
+                if neighbour != self.position && grove.contains_key(&neighbour) {
+                    return false;
+                }
+            }
         }
+        true
     }
 
-    fn can_move(&self, dir: i32, grove: &HashMap<(i32, i32), Elf>) -> bool {
-        match dir {
-            0 => {
-                !(grove.contains_key(&(self.position.0 - 1, self.position.1 - 1))
-                    || grove.contains_key(&(self.position.0 - 1, self.position.1))
-                    || grove.contains_key(&(self.position.0 - 1, self.position.1 + 1)))
-            }
-            1 => {
-                !(grove.contains_key(&(self.position.0 + 1, self.position.1 - 1))
-                    || grove.contains_key(&(self.position.0 + 1, self.position.1))
-                    || grove.contains_key(&(self.position.0 + 1, self.position.1 + 1)))
-            }
-            2 => {
-                !(grove.contains_key(&(self.position.0 - 1, self.position.1 - 1))
-                    || grove.contains_key(&(self.position.0, self.position.1 - 1))
-                    || grove.contains_key(&(self.position.0 + 1, self.position.1 - 1)))
-            }
-            3 => {
-                !(grove.contains_key(&(self.position.0 - 1, self.position.1 + 1))
-                    || grove.contains_key(&(self.position.0, self.position.1 + 1))
-                    || grove.contains_key(&(self.position.0 + 1, self.position.1 + 1)))
-            }
-            _ => panic!("Invalid direction"),
-        }
+    // The cell in the direction, and those either side of it, are empty.
+    fn can_move(&self, dir: Heading, grove: &HashMap<Point, Elf>) -> bool {
+        let ahead = self.position.step(dir);
+        !(grove.contains_key(&ahead)
+            || grove.contains_key(&ahead.step(dir.turn_left()))
+            || grove.contains_key(&ahead.step(dir.turn_right())))
     }
 
-    fn proposed_move(&self, grove: &HashMap<(i32, i32), Elf>) -> (i32, i32) {
+    fn proposed_move(&self, grove: &HashMap<Point, Elf>) -> Point {
         if self.alone(grove) {
             return self.position;
         }
-        let mut check_dir = self.next_dir;
-        for _ in 0..4 {
-            if self.can_move(check_dir, grove) {
-                return self.move_pos(check_dir);
+        for idx in 0..ORDER.len() {
+            let dir = ORDER[(self.next_dir + idx) % ORDER.len()];
+            if self.can_move(dir, grove) {
+                return self.position.step(dir);
             }
-            check_dir = (check_dir + 1) % 4;
         }
         self.position
     }
 
-    fn update(&mut self, pos: (i32, i32)) {
+    fn update(&mut self, pos: Point) {
         self.position = pos;
-        self.next_dir = (self.next_dir + 1) % 4;
+        self.next_dir = (self.next_dir + 1) % ORDER.len();
     }
 }
 
-#[derive(Debug)]
-struct Grove {
-    elves: HashMap<(i32, i32), Elf>,
+#[derive(Debug, Clone)]
+pub struct Grove {
+    elves: HashMap<Point, Elf>,
 }
 
 impl Grove {
-    fn new(filename: &str) -> Self {
-        let mut elves = HashMap::new();
-
-        for (row_idx, line) in read_list(filename).iter().enumerate() {
-            let row_idx = row_idx as i32;
-            for (col_idx, char) in line.chars().enumerate() {
-                let col_idx = col_idx as i32;
-                if char == '#' {
-                    elves.insert((row_idx, col_idx), Elf::new((row_idx, col_idx)));
-                }
-            }
-        }
-        Self { elves }
+    fn new(input: &str) -> Result<Self> {
+        let grid = Grid::parse(input, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+        // Elves spread out beyond the scanned area, so are kept sparse
+        let elves = grid
+            .iter()
+            .filter(|(_, &elf)| elf)
+            .map(|(pos, _)| {
+                let position = Point::from(pos);
+                (position, Elf::new(position))
+            })
+            .collect();
+        Ok(Self { elves })
     }
 
     fn round(&mut self) -> bool {
-        let mut proposed: HashMap<(i32, i32), i32> = HashMap::new();
+        let mut proposed: HashMap<Point, i32> = HashMap::new();
         for elf in self.elves.values() {
             if !elf.alone(&self.elves) {
                 let maybe_next_pos = elf.proposed_move(&self.elves);
@@ -126,7 +104,7 @@ impl Grove {
             return true;
         }
 
-        let mut updated_elves: HashMap<(i32, i32), Elf> = HashMap::new();
+        let mut updated_elves: HashMap<Point, Elf> = HashMap::new();
         for elf in self.elves.values().clone() {
             let proposal = elf.proposed_move(&self.elves);
             if proposed.get(&proposal) == Some(&1) {
@@ -150,35 +128,31 @@ impl Grove {
         let mut min_col = i32::MAX;
         let mut max_col = i32::MIN;
         for elf in self.elves.values() {
-            if elf.position.0 < min_row {
-                min_row = elf.position.0;
+            if elf.position.y < min_row {
+                min_row = elf.position.y;
             }
-            if elf.position.0 > max_row {
-                max_row = elf.position.0;
+            if elf.position.y > max_row {
+                max_row = elf.position.y;
             }
-            if elf.position.1 < min_col {
-                min_col = elf.position.1;
+            if elf.position.x < min_col {
+                min_col = elf.position.x;
             }
-            if elf.position.1 > max_col {
-                max_col = elf.position.1;
+            if elf.position.x > max_col {
+                max_col = elf.position.x;
             }
         }
         ((min_row, max_row), (min_col, max_col))
     }
 
-    #[allow(dead_code)]
-    fn display(&self) {
+    fn render(&self) -> Grid<char> {
         let ((min_row, max_row), (min_col, max_col)) = self.extents();
-        for row in min_row..=max_row {
-            for col in min_col..=max_col {
-                if self.elves.contains_key(&(row, col)) {
-                    print!("#");
-                } else {
-                    print!(".");
-                }
-            }
-            println!();
+        let width = (max_col - min_col + 1) as usize;
+        let height = (max_row - min_row + 1) as usize;
+        let mut grid = Grid::new(width, height, '.');
+        for elf in self.elves.keys() {
+            grid[((elf.y - min_row) as usize, (elf.x - min_col) as usize)] = '#';
         }
+        grid
     }
 
     fn empty_ground(&self) -> i32 {
@@ -190,22 +164,77 @@ impl Grove {
     }
 }
 
-pub fn step1() {
-    let mut grove = Grove::new("inputs/day23.txt");
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Grove;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day23.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        Grove::new(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<i32> {
+        let mut grove = input.clone();
+
+        for round in 1..=10 {
+            grove.round();
+            crate::trace!("After round {}:\n{}", round, grove.render());
+        }
+
+        Ok(grove.empty_ground())
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        let mut grove = input.clone();
+
+        let mut count = 1; // Wouldn't do to have an off-by-one answer...
+        while !grove.round() {
+            count += 1;
+        }
 
-    for _ in 0..10 {
-        grove.round();
+        Ok(count)
     }
 
-    println!("Empty ground: {}", grove.empty_ground());
+    // A `size` by `size` scan, about half of it elves
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let n = size.max(1);
+        let mut input = String::new();
+        for row in 0..n {
+            for col in 0..n {
+                // At least one elf, in the middle
+                let elf = (row, col) == (n / 2, n / 2) || rng.chance(45);
+                input.push(if elf { '#' } else { '.' });
+            }
+            input.push('\n');
+        }
+        Generated::new(input)
+    }
 }
-pub fn step2() {
-    let mut grove = Grove::new("inputs/day23.txt");
 
-    let mut count = 1; // Wouldn't do to have an off-by-one answer...
-    while !grove.round() {
-        count += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day23::part1(&example_input::<Day23>()).unwrap(), 110);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day23::part2(&example_input::<Day23>()).unwrap(), 20);
     }
 
-    println!("Rounds till finished: {}", count);
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day23>(23);
+        assert_eq!(Day23::part1(&input).unwrap(), 4056);
+        assert_eq!(Day23::part2(&input).unwrap(), 999);
+    }
 }
@@ -1,183 +1,219 @@
 // Blizzard Basin
-use std::{
-    collections::HashSet,
-    fs::File,
-    io::{BufRead, BufReader},
+use crate::{
+    error::{Error, Result},
+    generate::{Generated, Rng},
+    geometry::{Heading, Point},
+    grid::Grid,
+    params::Params,
+    search::{bfs, Graph},
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
-#[derive(Debug, Clone, Copy)]
-enum Blizzard {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-struct Maze {
-    blizzards: Vec<(i32, i32, Blizzard)>,
+pub struct Maze {
+    // The heading of the blizzard starting at each position within the walls
+    blizzards: Grid<Option<Heading>>,
     width: i32,
     height: i32,
+    // Minutes before the blizzards are back where they started
+    period: usize,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
 }
 
 impl Maze {
-    fn new(filename: &str) -> Self {
-        let mut blizzards: Vec<(i32, i32, Blizzard)> = vec![];
-        let mut width = 0;
-        let mut height = 0;
-        for (row_idx, line) in read_list(filename).iter().enumerate() {
-            let row_idx = row_idx as i32 - 1;
-            if row_idx > height {
-                height = row_idx;
-            }
-            for (col_idx, c) in line.chars().enumerate() {
-                let col_idx = col_idx as i32 - 1;
-                if col_idx > width {
-                    width = col_idx;
-                }
-                if let Some(blizzard) = match c {
-                    '<' => Some(Blizzard::Left),
-                    '>' => Some(Blizzard::Right),
-                    '^' => Some(Blizzard::Up),
-                    'v' => Some(Blizzard::Down),
-                    _ => None,
-                } {
-                    blizzards.push((row_idx, col_idx, blizzard));
-                }
+    fn new(input: &str) -> Result<Self> {
+        let grid = Grid::parse(input, |c| match c {
+            '#' | '.' | '<' | '>' | '^' | 'v' => Some(c),
+            _ => None,
+        })?;
+        if grid.width() < 3 || grid.height() < 3 {
+            return Err(Error::new("expected a valley surrounded by walls"));
+        }
+        let mut blizzards = Grid::new(grid.width() - 2, grid.height() - 2, None);
+        for ((row, col), c) in grid.iter() {
+            let blizzard = match c {
+                '<' => Heading::West,
+                '>' => Heading::East,
+                '^' => Heading::North,
+                'v' => Heading::South,
+                _ => continue,
+            };
+            // Blizzards are always inside the surrounding wall
+            if row == 0 || col == 0 || row == grid.height() - 1 || col == grid.width() - 1 {
+                return Err(grid.error_at((row, col), "blizzards must be inside the walls"));
             }
+            blizzards[(row - 1, col - 1)] = Some(blizzard);
         }
+        let (width, height) = (blizzards.width(), blizzards.height());
 
-        Self {
+        Ok(Self {
             blizzards,
-            width,
-            height,
+            width: width as i32,
+            height: height as i32,
+            period: width / gcd(width, height) * height,
+        })
+    }
+
+    // Blizzards wrap around within the walls, so the one which could be at
+    // a position after some minutes started that many places back.
+    fn blizzard_at(&self, pos: Point, minute: usize) -> bool {
+        if pos.y < 0 || pos.y >= self.height {
+            return false;
         }
+        let t = (minute % self.period) as i32;
+        let (row, col) = (pos.y as usize, pos.x as usize);
+        let from_col = |x: i32| x.rem_euclid(self.width) as usize;
+        let from_row = |y: i32| y.rem_euclid(self.height) as usize;
+        self.blizzards[(row, from_col(pos.x + t))] == Some(Heading::West)
+            || self.blizzards[(row, from_col(pos.x - t))] == Some(Heading::East)
+            || self.blizzards[(from_row(pos.y + t), col)] == Some(Heading::North)
+            || self.blizzards[(from_row(pos.y - t), col)] == Some(Heading::South)
     }
 
-    fn advance(&self, blizzards: &[(i32, i32, Blizzard)]) -> Vec<(i32, i32, Blizzard)> {
-        let mut new_blizzards = vec![];
+    // Within the walls, or at one of the gaps
+    fn open(&self, pos: Point) -> bool {
+        (pos.x >= 0 && pos.x < self.width && pos.y >= 0 && pos.y < self.height)
+            || pos == self.entrance()
+            || pos == self.exit()
+    }
 
-        for (row, col, dir) in blizzards.iter().copied() {
-            match dir {
-                Blizzard::Left => {
-                    new_blizzards.push((row, (col + self.width - 1) % self.width, dir));
-                }
-                Blizzard::Right => {
-                    new_blizzards.push((row, (col + 1) % self.width, dir));
-                }
-                Blizzard::Up => {
-                    new_blizzards.push(((row + self.height - 1) % self.height, col, dir));
-                }
-                Blizzard::Down => {
-                    new_blizzards.push(((row + 1) % self.height, col, dir));
-                }
-            }
-        }
+    /// Gap in the top wall where the expedition starts.
+    pub fn entrance(&self) -> Point {
+        Point::new(0, -1)
+    }
 
-        new_blizzards
+    /// Gap in the bottom wall.
+    pub fn exit(&self) -> Point {
+        Point::new(self.width - 1, self.height)
     }
 
-    fn blizzard_at(&self, pos: (i32, i32), blizzards: &[(i32, i32, Blizzard)]) -> bool {
-        blizzards.iter().any(|(r, c, _)| (*r, *c) == pos)
+    /// Minutes to visit each of the targets in turn, from the entrance.
+    pub fn solve(&self, targets: &[Point]) -> Result<i32> {
+        self.route(targets)
+            .ok_or_else(|| Error::new("no route to the target"))
     }
 
-    fn move_options(&self, pos: (i32, i32), blizzards: &[(i32, i32, Blizzard)]) -> Vec<(i32, i32)> {
-        let mut options = vec![];
-        // Move right
-        if pos.0 >= 0 && pos.1 < self.width - 1 {
-            let candidate = (pos.0, pos.1 + 1);
-            if !self.blizzard_at(candidate, blizzards) {
-                options.push(candidate);
-            }
-        }
-        // Move down
-        if pos.0 < self.height - 1 || pos.1 == self.width - 1 {
-            let candidate = (pos.0 + 1, pos.1);
-            if !self.blizzard_at(candidate, blizzards) {
-                options.push(candidate);
-            }
-        }
-        // Stay still
-        if (pos.0 >= 0 || pos == (-1, 0) || pos == (self.height, self.width - 1))
-            && !self.blizzard_at(pos, blizzards)
-        {
-            options.push(pos);
-        }
-        // Move up
-        if pos.0 > 0 || pos == (0, 0) {
-            let candidate = (pos.0 - 1, pos.1);
-            if !self.blizzard_at(candidate, blizzards) {
-                options.push(candidate);
-            }
-        }
-        // Move left
-        if pos.0 >= 0 && pos.0 < self.height && pos.1 > 0 {
-            let candidate = (pos.0, pos.1 - 1);
-            if !self.blizzard_at(candidate, blizzards) {
-                options.push(candidate);
-            }
+    // As for `solve`, or None when the blizzards wall a target off.
+    fn route(&self, targets: &[Point]) -> Option<i32> {
+        let mut minutes = 0;
+        let mut position = self.entrance();
+        for &target in targets {
+            let search = bfs(self, [(position, minutes % self.period)], |&(pos, _)| {
+                pos == target
+            });
+            minutes += search.target_distance()?;
+            crate::info!("Reached target {} in {} minutes", target, minutes);
+            position = target;
         }
-        options
+        Some(minutes as i32)
     }
+}
 
-    fn solve(&self, targets: &[(i32, i32)]) -> i32 {
-        let mut minutes = 0;
-        let mut positions = vec![(-1, 0)];
-        let mut blizzards = self.blizzards.clone();
+// Positions at a minute into the blizzards' cycle.
+impl Graph for Maze {
+    type Node = (Point, usize);
+
+    // Moving or waiting, to somewhere clear of blizzards in the next minute
+    fn neighbours(&self, &(pos, minute): &(Point, usize)) -> Vec<(Point, usize)> {
+        let next_minute = (minute + 1) % self.period;
+        Heading::ALL
+            .iter()
+            .map(|&heading| pos.step(heading))
+            .chain([pos])
+            .filter(|&next| self.open(next) && !self.blizzard_at(next, next_minute))
+            .map(|next| (next, next_minute))
+            .collect()
+    }
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    type Input = Maze;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day24.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        Maze::new(input)
+    }
 
-        let mut target_idx = 0;
+    fn part1(input: &Self::Input) -> Result<i32> {
+        // Maze escape time
+        let targets = [input.exit()];
+        input.solve(&targets)
+    }
+
+    fn part2(input: &Self::Input) -> Result<i32> {
+        // Maze escape there, back again, and there again
+        let targets = [input.exit(), input.entrance(), input.exit()];
+        input.solve(&targets)
+    }
 
+    // A valley `size` rows high and five times as wide, a quarter of it
+    // blizzards. Blizzards can block a column or row at every minute, so
+    // valleys are drawn again until both parts have a route.
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let height = size.max(2);
+        let width = 5 * height;
         loop {
-            minutes += 1;
-            let mut next_positions = HashSet::new();
-            blizzards = self.advance(&blizzards);
-            let b = blizzards.clone();
-            for pos in &positions {
-                for option in self.move_options(*pos, &b) {
-                    next_positions.insert(option);
+            let mut input = format!("#.{}\n", "#".repeat(width));
+            for _ in 0..height {
+                input.push('#');
+                for _ in 0..width {
+                    input.push(match rng.chance(25) {
+                        true => *rng.choose(&['<', '>', '^', 'v']),
+                        false => '.',
+                    });
                 }
+                input += "#\n";
             }
-            for b in &blizzards {
-                if next_positions.contains(&(b.0, b.1)) {
-                    next_positions.remove(&(b.0, b.1));
-                }
+            input += &format!("{}.#\n", "#".repeat(width));
+            let maze = Maze::new(&input).expect("generated valleys are valid");
+            if maze
+                .route(&[maze.exit(), maze.entrance(), maze.exit()])
+                .is_some()
+            {
+                return Generated::new(input);
             }
-            if next_positions.contains(&targets[target_idx]) {
-                next_positions.clear();
-                next_positions.insert(targets[target_idx]);
-                //println!("Reached target {} in {} minutes", target_idx, minutes);
-                target_idx += 1;
-                if target_idx == targets.len() {
-                    break;
-                }
-            }
-            positions = next_positions.into_iter().collect();
         }
-        minutes
     }
 }
 
-pub fn step1() {
-    let maze = Maze::new("inputs/day24.txt");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
 
-    let targets = [(maze.height, maze.width - 1)];
-    println!("Maze escape time: {}", maze.solve(&targets));
-}
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day24::part1(&example_input::<Day24>()).unwrap(), 18);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day24::part2(&example_input::<Day24>()).unwrap(), 54);
+    }
 
-pub fn step2() {
-    let maze = Maze::new("inputs/day24.txt");
-
-    let targets = [
-        (maze.height, maze.width - 1),
-        (-1, 0),
-        (maze.height, maze.width - 1),
-    ];
-    println!(
-        "Maze escape there, back again, and there again: {}",
-        maze.solve(&targets)
-    );
+    #[test]
+    fn walled_off() {
+        // The blizzard never leaves the only way through
+        let valley = Day24::parse("#.#\n#v#\n#.#\n", &Params::new(Day24::PARAMS, false));
+        assert!(Day24::part1(&valley.unwrap()).is_err());
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day24>(24);
+        assert_eq!(Day24::part1(&input).unwrap(), 288);
+        assert_eq!(Day24::part2(&input).unwrap(), 861);
+    }
 }
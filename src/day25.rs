@@ -1,19 +1,21 @@
 // Full of Hot Air
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    input::numbered_lines,
+    params::Params,
+    solution::Solution,
 };
 
-pub fn read_list(filename: &str) -> Vec<String> {
-    let f = File::open(filename).expect("Could not read file");
-    BufReader::new(f).lines().map(|l| l.expect("Err")).collect()
-}
-
-fn from_snafu(s: &str) -> i64 {
+/// The value of a SNAFU number, or the index of its first bad digit.
+pub fn from_snafu(s: &str) -> std::result::Result<i64, usize> {
+    if let Some(idx) = s.find(|c| !"210-=".contains(c)) {
+        return Err(idx);
+    }
     let mut total = 0;
 
     let mut placevalue = 1;
-    for c in s.chars().into_iter().rev() {
+    for c in s.chars().rev() {
         match c {
             '1' => {
                 total += placevalue;
@@ -27,12 +29,11 @@ fn from_snafu(s: &str) -> i64 {
             '-' => {
                 total -= placevalue;
             }
-            '0' => {}
-            _ => panic!("Unknown digit"),
+            _ => {}
         }
         placevalue *= 5;
     }
-    total
+    Ok(total)
 }
 
 pub fn to_snafu(v: i64) -> String {
@@ -60,12 +61,65 @@ pub fn to_snafu(v: i64) -> String {
     String::from_iter(result.iter())
 }
 
-pub fn step1() {
-    let result: i64 = read_list("inputs/day25.txt")
-        .iter()
-        .map(|x| from_snafu(x))
-        .sum();
-    println!("result (decimal): {}", result);
-    println!("result (snafu): {}", to_snafu(result));
+pub struct Day25;
+
+impl Solution for Day25 {
+    type Input = Vec<i64>;
+    type Answer1 = String;
+    type Answer2 = &'static str;
+
+    const EXAMPLE: &'static str = include_str!("../inputs/examples/day25.txt");
+
+    fn parse(input: &str, _params: &Params) -> Result<Self::Input> {
+        numbered_lines(input)?
+            .iter()
+            .map(|line| {
+                from_snafu(line.text)
+                    .map_err(|idx| line.char_error(idx, "SNAFU digits must be 2, 1, 0, - or ="))
+            })
+            .collect()
+    }
+
+    fn part1(input: &Self::Input) -> Result<String> {
+        let result: i64 = input.iter().sum();
+        crate::info!("result (decimal): {}", result);
+        Ok(to_snafu(result))
+    }
+
+    fn part2(_input: &Self::Input) -> Result<&'static str> {
+        // Day 25 only has one puzzle
+        Ok("")
+    }
+
+    // `size` fuel requirements
+    fn generate(rng: &mut Rng, size: usize) -> Generated {
+        let numbers: Vec<String> = (0..size.max(1))
+            .map(|_| to_snafu(rng.range(1..=1000000000000)))
+            .collect();
+        Generated::new(numbers.join("\n") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{committed_input, example_input};
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(Day25::part1(&example_input::<Day25>()).unwrap(), "2=-1=0");
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(Day25::part2(&example_input::<Day25>()).unwrap(), "");
+    }
+
+    #[test]
+    #[ignore = "uses the committed puzzle input"]
+    fn puzzle_input() {
+        let input = committed_input::<Day25>(25);
+        assert_eq!(Day25::part1(&input).unwrap(), "121=2=1==0=10=2-20=2");
+        assert_eq!(Day25::part2(&input).unwrap(), "");
+    }
 }
-pub fn step2() {}
@@ -0,0 +1,86 @@
+use std::{fmt, io};
+
+/// An error reading or parsing puzzle input, with as much location
+/// information as is known.
+#[derive(Debug)]
+pub struct Error {
+    pub message: String,
+    pub day: Option<usize>,
+    /// 1-based line number within the input.
+    pub line: Option<usize>,
+    /// 1-based character column within the line.
+    pub column: Option<usize>,
+    /// The offending line of input.
+    pub text: Option<String>,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            day: None,
+            line: None,
+            column: None,
+            text: None,
+        }
+    }
+
+    pub fn at_line(mut self, line: usize, text: &str) -> Self {
+        self.line = Some(line);
+        self.text = Some(text.to_string());
+        self
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column = Some(column);
+        self
+    }
+
+    pub fn in_day(mut self, day: usize) -> Self {
+        self.day = Some(day);
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut location = vec![];
+        if let Some(day) = self.day {
+            location.push(format!("day {:02}", day));
+        }
+        if let Some(line) = self.line {
+            location.push(format!("line {}", line));
+        }
+        if let Some(column) = self.column {
+            location.push(format!("column {}", column));
+        }
+        if !location.is_empty() {
+            write!(f, "{}: ", location.join(", "))?;
+        }
+        write!(f, "{}", self.message)?;
+
+        if let Some(text) = &self.text {
+            write!(f, "\n    {}", text)?;
+            if let Some(column) = self.column {
+                // Point at the offending character
+                let indent: String = text
+                    .chars()
+                    .take(column - 1)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                write!(f, "\n    {}^", indent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::new(e.to_string())
+    }
+}
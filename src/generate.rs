@@ -0,0 +1,151 @@
+use std::ops::RangeInclusive;
+
+use crate::solution::Solution;
+
+/// SplitMix64: small and fast, random enough for test inputs, and the same
+/// sequence for a seed on every platform and build.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`, which mustn't be empty.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (low, high) = (*range.start(), *range.end());
+        assert!(low <= high, "empty range");
+        let span = (high - low) as u64 as u128 + 1;
+        low + ((self.next_u64() as u128 * span) >> 64) as i64
+    }
+
+    /// True `percent` times out of 100.
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.below(idx + 1));
+        }
+    }
+}
+
+/// A generated puzzle input, and any parameters it must be solved with.
+#[derive(Debug, Clone, Default)]
+pub struct Generated {
+    pub input: String,
+    pub params: Vec<(&'static str, String)>,
+}
+
+impl Generated {
+    pub fn new(input: String) -> Self {
+        Self {
+            input,
+            params: vec![],
+        }
+    }
+
+    pub fn with_param(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.params.push((name, value.to_string()));
+        self
+    }
+}
+
+/// A day's input generator, from a seed and a size.
+pub type Generator = fn(u64, usize) -> Generated;
+
+pub fn generate<S: Solution>(seed: u64, size: usize) -> Generated {
+    S::generate(&mut Rng::new(seed), size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day01::Day01, day02::Day02, day03::Day03, day04::Day04, day05::Day05, day06::Day06,
+        day07::Day07, day08::Day08, day09::Day09, day10::Day10, day11::Day11, day12::Day12,
+        day13::Day13, day14::Day14, day15::Day15, day16::Day16, day17::Day17, day18::Day18,
+        day19::Day19, day20::Day20, day21::Day21, day22::Day22, day23::Day23, day24::Day24,
+        day25::Day25, params::Params,
+    };
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+            let value = a.range(-3..=3);
+            assert_eq!(value, b.range(-3..=3));
+            assert!((-3..=3).contains(&value));
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    // Both parts solve small and tiny inputs
+    fn solves<S: Solution>() -> bool {
+        [1, 8].iter().all(|&size| {
+            (0..5).all(|seed| {
+                let generated = generate::<S>(seed, size);
+                let mut params = Params::new(S::PARAMS, false);
+                for (name, value) in &generated.params {
+                    params.set(name, value).unwrap();
+                }
+                match S::parse(&generated.input, &params) {
+                    Ok(input) => S::part1(&input).is_ok() && S::part2(&input).is_ok(),
+                    Err(_) => false,
+                }
+            })
+        })
+    }
+
+    #[test]
+    fn generated_inputs_solve() {
+        assert!(solves::<Day01>());
+        assert!(solves::<Day02>());
+        assert!(solves::<Day03>());
+        assert!(solves::<Day04>());
+        assert!(solves::<Day05>());
+        assert!(solves::<Day06>());
+        assert!(solves::<Day07>());
+        assert!(solves::<Day08>());
+        assert!(solves::<Day09>());
+        assert!(solves::<Day10>());
+        assert!(solves::<Day11>());
+        assert!(solves::<Day12>());
+        assert!(solves::<Day13>());
+        assert!(solves::<Day14>());
+        assert!(solves::<Day15>());
+        assert!(solves::<Day16>());
+        assert!(solves::<Day17>());
+        assert!(solves::<Day18>());
+        assert!(solves::<Day19>());
+        assert!(solves::<Day20>());
+        assert!(solves::<Day21>());
+        assert!(solves::<Day22>());
+        assert!(solves::<Day23>());
+        assert!(solves::<Day24>());
+        assert!(solves::<Day25>());
+    }
+}
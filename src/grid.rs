@@ -0,0 +1,283 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use crate::{
+    error::{Error, Result},
+    input::{numbered_lines, Line},
+};
+
+/// A position in a grid, as (row, column) from the top left.
+pub type Pos = (usize, usize);
+
+/// Offsets of the 4 orthogonal neighbours.
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Offsets of all 8 neighbours, including diagonals.
+const ALL_AROUND: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A dense rectangular grid, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Lines of a character map, padding short lines with `pad`.
+    ///
+    /// `cell` gives the value of each character, or None for characters
+    /// which aren't allowed.
+    pub fn from_lines(
+        lines: &[Line],
+        pad: T,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self> {
+        let width = lines
+            .iter()
+            .map(|l| l.text.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut grid = Self::new(width, lines.len(), pad);
+        for (row, line) in lines.iter().enumerate() {
+            for (col, c) in line.text.chars().enumerate() {
+                grid[(row, col)] = cell(c)
+                    .ok_or_else(|| line.char_error(col, format!("unexpected character '{}'", c)))?;
+            }
+        }
+        Ok(grid)
+    }
+}
+
+impl<T> Grid<T> {
+    /// A rectangular character map; every line must be the same width.
+    pub fn parse(input: &str, mut cell: impl FnMut(char) -> Option<T>) -> Result<Self> {
+        let lines = numbered_lines(input)?;
+        let width = lines[0].text.chars().count();
+        let mut cells = vec![];
+        for line in &lines {
+            let mut count = 0;
+            for (col, c) in line.text.chars().enumerate() {
+                let value = cell(c)
+                    .ok_or_else(|| line.char_error(col, format!("unexpected character '{}'", c)))?;
+                cells.push(value);
+                count += 1;
+            }
+            if count != width {
+                return Err(line.error(format!("expected {} columns, found {}", width, count)));
+            }
+        }
+        Ok(Self {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        if pos.0 < self.height && pos.1 < self.width {
+            Some(&self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if pos.0 < self.height && pos.1 < self.width {
+            Some(&mut self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// The position at signed coordinates, if it's within the grid.
+    pub fn checked_pos(&self, row: i64, col: i64) -> Option<Pos> {
+        if row >= 0 && col >= 0 && (row as usize) < self.height && (col as usize) < self.width {
+            Some((row as usize, col as usize))
+        } else {
+            None
+        }
+    }
+
+    fn offset_neighbours<'a>(
+        &'a self,
+        pos: Pos,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        offsets.iter().filter_map(move |(dr, dc)| {
+            let row = pos.0.checked_add_signed(*dr)?;
+            let col = pos.1.checked_add_signed(*dc)?;
+            self.get((row, col)).map(|_| (row, col))
+        })
+    }
+
+    /// Up, right, down and left neighbours which are within the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.offset_neighbours(pos, &ORTHOGONAL)
+    }
+
+    /// All eight surrounding neighbours which are within the grid.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.offset_neighbours(pos, &ALL_AROUND)
+    }
+
+    pub fn row(&self, row: usize) -> std::slice::Iter<'_, T> {
+        self.cells[row * self.width..(row + 1) * self.width].iter()
+    }
+
+    pub fn column(&self, col: usize) -> std::iter::StepBy<std::slice::Iter<'_, T>> {
+        self.cells[col..].iter().step_by(self.width)
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        self.cells.chunks(self.width)
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Every position with its cell, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The first position, row by row, whose cell matches.
+    pub fn find(&self, mut pred: impl FnMut(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, c)| pred(c)).map(|(pos, _)| pos)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// An error pointing at a cell of a grid parsed from the first lines
+    /// of the input.
+    pub fn error_at(&self, pos: Pos, message: &str) -> Error
+    where
+        T: fmt::Display,
+    {
+        let text: String = self.row(pos.0).map(|c| c.to_string()).collect();
+        Error::new(message)
+            .at_line(pos.0 + 1, &text)
+            .at_column(pos.1 + 1)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        assert!(pos.0 < self.height && pos.1 < self.width);
+        &self.cells[pos.0 * self.width + pos.1]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        assert!(pos.0 < self.height && pos.1 < self.width);
+        &mut self.cells[pos.0 * self.width + pos.1]
+    }
+}
+
+/// Each cell's `Display`, one line per row.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, row) in self.rows().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits() -> Grid<u32> {
+        Grid::parse("123\n456\n", |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = digits();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 2)], 6);
+        assert_eq!(grid.to_string(), "123\n456");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let e = Grid::parse("123\n4x6\n", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!((e.line, e.column), (Some(2), Some(2)));
+        let e = Grid::parse("123\n45\n", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(e.line, Some(2));
+    }
+
+    #[test]
+    fn padded_lines() {
+        let lines = numbered_lines("  #\n#").unwrap();
+        let grid = Grid::from_lines(&lines, ' ', Some).unwrap();
+        assert_eq!(grid.to_string(), "  #\n#  ");
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = digits();
+        let n4: Vec<Pos> = grid.neighbours4((0, 0)).collect();
+        assert_eq!(n4, vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 5);
+        assert_eq!(grid.neighbours8((0, 1)).count(), 5);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = digits();
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), vec![4, 5, 6]);
+        assert_eq!(
+            grid.column(2).rev().copied().collect::<Vec<_>>(),
+            vec![6, 3]
+        );
+        assert_eq!(grid.find(|&d| d == 5), Some((1, 1)));
+        assert_eq!(grid.checked_pos(-1, 0), None);
+        assert_eq!(grid.checked_pos(1, 2), Some((1, 2)));
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use crate::error::{Error, Result};
+
+/// Where a day's puzzle input comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+    /// The worked example from the puzzle text, embedded in the binary.
+    Example,
+    /// The committed puzzle input for a day, read from `inputs/` or, with
+    /// the `embed-inputs` feature, built into the binary so it runs from
+    /// any directory.
+    Committed(usize),
+}
+
+#[cfg(feature = "embed-inputs")]
+const EMBEDDED: [&str; 25] = [
+    include_str!("../inputs/day01.txt"),
+    include_str!("../inputs/day02.txt"),
+    include_str!("../inputs/day03.txt"),
+    include_str!("../inputs/day04.txt"),
+    include_str!("../inputs/day05.txt"),
+    include_str!("../inputs/day06.txt"),
+    include_str!("../inputs/day07.txt"),
+    include_str!("../inputs/day08.txt"),
+    include_str!("../inputs/day09.txt"),
+    include_str!("../inputs/day10.txt"),
+    include_str!("../inputs/day11.txt"),
+    include_str!("../inputs/day12.txt"),
+    include_str!("../inputs/day13.txt"),
+    include_str!("../inputs/day14.txt"),
+    include_str!("../inputs/day15.txt"),
+    include_str!("../inputs/day16.txt"),
+    include_str!("../inputs/day17.txt"),
+    include_str!("../inputs/day18.txt"),
+    include_str!("../inputs/day19.txt"),
+    include_str!("../inputs/day20.txt"),
+    include_str!("../inputs/day21.txt"),
+    include_str!("../inputs/day22.txt"),
+    include_str!("../inputs/day23.txt"),
+    include_str!("../inputs/day24.txt"),
+    include_str!("../inputs/day25.txt"),
+];
+
+impl InputSource {
+    /// Command-line form: `-` is stdin, anything else is a file path.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            InputSource::Stdin
+        } else {
+            InputSource::File(PathBuf::from(arg))
+        }
+    }
+
+    /// The committed puzzle input for the given day.
+    pub fn default_for_day(day: usize) -> Self {
+        InputSource::Committed(day)
+    }
+
+    pub fn read(&self, example: &str) -> Result<String> {
+        match self {
+            InputSource::File(path) => fs::read_to_string(path)
+                .map_err(|e| Error::new(format!("{}: {}", path.display(), e))),
+            InputSource::Stdin => {
+                let mut buffer = String::new();
+                io::stdin().read_to_string(&mut buffer)?;
+                Ok(buffer)
+            }
+            InputSource::Example => Ok(example.to_string()),
+            InputSource::Committed(day) => read_committed(*day),
+        }
+    }
+}
+
+#[cfg(not(feature = "embed-inputs"))]
+fn read_committed(day: usize) -> Result<String> {
+    InputSource::File(PathBuf::from(format!("inputs/day{:02}.txt", day))).read("")
+}
+
+#[cfg(feature = "embed-inputs")]
+fn read_committed(day: usize) -> Result<String> {
+    match day.checked_sub(1).and_then(|idx| EMBEDDED.get(idx)) {
+        Some(text) => Ok(text.to_string()),
+        None => Err(Error::new(format!("no embedded input for day {}", day))),
+    }
+}
+
+/// A line of input with its 1-based line number, for error reporting.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn error(&self, message: impl Into<String>) -> Error {
+        Error::new(message).at_line(self.number, self.text)
+    }
+
+    /// An error pointing at `field`, which should be a slice of this line.
+    pub fn field_error(&self, field: &str, message: impl Into<String>) -> Error {
+        let error = self.error(message);
+        let start = self.text.as_ptr() as usize;
+        let offset = (field.as_ptr() as usize).wrapping_sub(start);
+        if offset <= self.text.len() {
+            let column = self.text[..offset].chars().count() + 1;
+            error.at_column(column)
+        } else {
+            error
+        }
+    }
+
+    pub fn char_error(&self, column_idx: usize, message: impl Into<String>) -> Error {
+        self.error(message).at_column(column_idx + 1)
+    }
+
+    /// Parse `field`, a slice of this line, reporting its column on failure.
+    pub fn parse<T: FromStr>(&self, field: &str) -> Result<T> {
+        field
+            .parse()
+            .map_err(|_| self.field_error(field, format!("invalid number '{}'", field)))
+    }
+
+    /// Unwrap a field extracted from this line, which must be present.
+    pub fn field(&self, field: Option<&'a str>, what: &str) -> Result<&'a str> {
+        field.ok_or_else(|| self.error(format!("missing {}", what)))
+    }
+}
+
+// Lines with CRLF endings and trailing whitespace removed, and without
+// any trailing blank lines.
+pub fn numbered_lines(input: &str) -> Result<Vec<Line<'_>>> {
+    let mut lines: Vec<Line> = input
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| Line {
+            number: idx + 1,
+            text: l.trim_end(),
+        })
+        .collect();
+    while lines.last().map(|l| l.text.is_empty()) == Some(true) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return Err(Error::new("empty input"));
+    }
+    Ok(lines)
+}
+
+/// Groups of lines separated by one or more blank lines.
+pub fn numbered_paragraphs(input: &str) -> Result<Vec<Vec<Line<'_>>>> {
+    let mut paragraphs = vec![];
+    let mut current = vec![];
+    for line in numbered_lines(input)? {
+        if line.text.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current);
+                current = vec![];
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    // numbered_lines() guarantees a non-blank final line
+    paragraphs.push(current);
+    Ok(paragraphs)
+}
+
+pub fn read_lines(input: &str) -> Result<Vec<String>> {
+    Ok(numbered_lines(input)?
+        .iter()
+        .map(|l| l.text.to_string())
+        .collect())
+}
+
+/// All characters of the input, excluding line breaks.
+pub fn read_chars(input: &str) -> Result<Vec<char>> {
+    Ok(numbered_lines(input)?
+        .iter()
+        .flat_map(|l| l.text.chars())
+        .collect())
+}
+
+/// One integer per line.
+pub fn read_ints<T: FromStr>(input: &str) -> Result<Vec<T>> {
+    numbered_lines(input)?
+        .iter()
+        .map(|line| line.parse(line.text.trim()))
+        .collect()
+}
@@ -0,0 +1,169 @@
+//! Advent of Code 2022 solutions.
+//!
+//! Each `dayNN` module exposes its parsed puzzle model and a `DayNN`
+//! type implementing [`solution::Solution`]. The days' input and answer
+//! types differ, so the command-line runner looks them up by day number
+//! in tables of type-erased functions: [`DAYS`] to solve, and
+//! [`GENERATORS`], [`CROSSCHECKS`] and [`REPORTS`].
+
+pub mod bench;
+pub mod crosscheck;
+pub mod cycle;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod error;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod log;
+pub mod output;
+pub mod parallel;
+pub mod params;
+pub mod search;
+pub mod solution;
+pub mod verify;
+
+use crosscheck::{crosscheck, Crosscheck};
+use generate::{generate, Generator};
+use solution::{report, solve, Reporter, Solver};
+
+/// Solver for each day, indexed by day number - 1.
+pub const DAYS: [Solver; 25] = [
+    solve::<day01::Day01>,
+    solve::<day02::Day02>,
+    solve::<day03::Day03>,
+    solve::<day04::Day04>,
+    solve::<day05::Day05>,
+    solve::<day06::Day06>,
+    solve::<day07::Day07>,
+    solve::<day08::Day08>,
+    solve::<day09::Day09>,
+    solve::<day10::Day10>,
+    solve::<day11::Day11>,
+    solve::<day12::Day12>,
+    solve::<day13::Day13>,
+    solve::<day14::Day14>,
+    solve::<day15::Day15>,
+    solve::<day16::Day16>,
+    solve::<day17::Day17>,
+    solve::<day18::Day18>,
+    solve::<day19::Day19>,
+    solve::<day20::Day20>,
+    solve::<day21::Day21>,
+    solve::<day22::Day22>,
+    solve::<day23::Day23>,
+    solve::<day24::Day24>,
+    solve::<day25::Day25>,
+];
+
+/// Input generator for each day, indexed by day number - 1.
+pub const GENERATORS: [Generator; 25] = [
+    generate::<day01::Day01>,
+    generate::<day02::Day02>,
+    generate::<day03::Day03>,
+    generate::<day04::Day04>,
+    generate::<day05::Day05>,
+    generate::<day06::Day06>,
+    generate::<day07::Day07>,
+    generate::<day08::Day08>,
+    generate::<day09::Day09>,
+    generate::<day10::Day10>,
+    generate::<day11::Day11>,
+    generate::<day12::Day12>,
+    generate::<day13::Day13>,
+    generate::<day14::Day14>,
+    generate::<day15::Day15>,
+    generate::<day16::Day16>,
+    generate::<day17::Day17>,
+    generate::<day18::Day18>,
+    generate::<day19::Day19>,
+    generate::<day20::Day20>,
+    generate::<day21::Day21>,
+    generate::<day22::Day22>,
+    generate::<day23::Day23>,
+    generate::<day24::Day24>,
+    generate::<day25::Day25>,
+];
+
+/// Crosscheck against the reference solvers for each day, indexed by day
+/// number - 1.
+pub const CROSSCHECKS: [Crosscheck; 25] = [
+    crosscheck::<day01::Day01>,
+    crosscheck::<day02::Day02>,
+    crosscheck::<day03::Day03>,
+    crosscheck::<day04::Day04>,
+    crosscheck::<day05::Day05>,
+    crosscheck::<day06::Day06>,
+    crosscheck::<day07::Day07>,
+    crosscheck::<day08::Day08>,
+    crosscheck::<day09::Day09>,
+    crosscheck::<day10::Day10>,
+    crosscheck::<day11::Day11>,
+    crosscheck::<day12::Day12>,
+    crosscheck::<day13::Day13>,
+    crosscheck::<day14::Day14>,
+    crosscheck::<day15::Day15>,
+    crosscheck::<day16::Day16>,
+    crosscheck::<day17::Day17>,
+    crosscheck::<day18::Day18>,
+    crosscheck::<day19::Day19>,
+    crosscheck::<day20::Day20>,
+    crosscheck::<day21::Day21>,
+    crosscheck::<day22::Day22>,
+    crosscheck::<day23::Day23>,
+    crosscheck::<day24::Day24>,
+    crosscheck::<day25::Day25>,
+];
+
+/// Report for each day, indexed by day number - 1.
+pub const REPORTS: [Reporter; 25] = [
+    report::<day01::Day01>,
+    report::<day02::Day02>,
+    report::<day03::Day03>,
+    report::<day04::Day04>,
+    report::<day05::Day05>,
+    report::<day06::Day06>,
+    report::<day07::Day07>,
+    report::<day08::Day08>,
+    report::<day09::Day09>,
+    report::<day10::Day10>,
+    report::<day11::Day11>,
+    report::<day12::Day12>,
+    report::<day13::Day13>,
+    report::<day14::Day14>,
+    report::<day15::Day15>,
+    report::<day16::Day16>,
+    report::<day17::Day17>,
+    report::<day18::Day18>,
+    report::<day19::Day19>,
+    report::<day20::Day20>,
+    report::<day21::Day21>,
+    report::<day22::Day22>,
+    report::<day23::Day23>,
+    report::<day24::Day24>,
+    report::<day25::Day25>,
+];
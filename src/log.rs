@@ -0,0 +1,46 @@
+//! Diagnostic output for following what a solver is doing, written to
+//! stderr so it never mixes with the answers. Off unless the level is
+//! raised, e.g. by `-v` or `-vv` on the command line.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How much detail to show; each level includes those before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Quiet = 0,
+    /// Intermediate results, such as each blueprint's geode count.
+    Info = 1,
+    /// Step by step detail, which may be a lot of output.
+    Trace = 2,
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Quiet as u8);
+
+/// Set the level for all threads; counts above `Trace` are capped.
+pub fn set_level(verbosity: u8) {
+    LEVEL.store(verbosity.min(Level::Trace as u8), Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    LEVEL.load(Ordering::Relaxed) >= level as u8
+}
+
+/// Like `eprintln!`, shown from `-v` up.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Info) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Like `eprintln!`, shown only with `-vv`.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::log::enabled($crate::log::Level::Trace) {
+            eprintln!($($arg)*);
+        }
+    };
+}
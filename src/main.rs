@@ -1,6 +1,488 @@
-mod day25;
+use std::{env, fs, path::PathBuf, process, time::Instant};
+
+use aoc2022::{
+    bench, crosscheck, error,
+    input::InputSource,
+    log,
+    output::{self, Format, Record},
+    parallel,
+    params::{self, Setting},
+    solution::Part,
+    verify, DAYS, GENERATORS, REPORTS,
+};
+
+const BENCH_REPS: usize = 5;
+const GENERATE_SIZE: usize = 10;
+const CROSSCHECK_SIZE: usize = 4;
+const CROSSCHECK_SEEDS: usize = 10;
+
+#[derive(Debug)]
+enum Command {
+    Day(usize),
+    All,
+    Verify,
+    // None to benchmark every day
+    Bench(Option<usize>),
+    Generate(usize),
+    // None to crosscheck every day with a reference solver
+    Crosscheck(Option<usize>),
+    Report(usize),
+}
+
+#[derive(Debug)]
+struct Options {
+    part: Part,
+    // None for the committed input of each day
+    source: Option<InputSource>,
+    // None for the default answers file of the input set
+    answers: Option<PathBuf>,
+    // None for BENCH_REPS, or CROSSCHECK_SEEDS
+    reps: Option<usize>,
+    // Also write the benchmark table here
+    output: Option<PathBuf>,
+    format: Format,
+    // Run days concurrently on this many threads, with a summary table
+    jobs: Option<usize>,
+    // Number of -v flags
+    verbosity: u8,
+    // Parameter overrides from --config then --param, so later ones win
+    settings: Vec<Setting>,
+    config: Option<PathBuf>,
+    // None for GENERATE_SIZE, or CROSSCHECK_SIZE
+    size: Option<usize>,
+    // None for seed 0
+    seed: Option<u64>,
+}
+
+const USAGE: &str = "\
+Usage: aoc2022 <day> [--part 1|2|both] [--input <path>|-] [--example]
+                    [--format text|json|csv] [--param <name>=<value>]...
+       aoc2022 all [--part 1|2|both] [--example] [--format text|json|csv]
+                   [--jobs <n>]
+       aoc2022 verify [--part 1|2|both] [--example] [--answers <path>]
+       aoc2022 bench <day>|all [--part 1|2|both] [--input <path>] [--example]
+                     [--reps <n>] [--output <path>] [--param <name>=<value>]...
+       aoc2022 generate <day> [--size <n>] [--seed <n>]
+       aoc2022 crosscheck <day>|all [--part 1|2|both] [--size <n>] [--seed <n>]
+                          [--reps <n>]
+       aoc2022 report <day> [--input <path>|-] [--example] [--param <name>=<value>]...
+
+Every command also takes --config <path>.
+
+Commands:
+  verify      Check answers against those recorded in an answers file
+  bench       Time parsing and each part, showing min/median/max
+  generate    Write a random input for the day to stdout, for stress testing,
+              and any parameters it needs to stderr
+  crosscheck  Compare answers with those of the slow reference solvers, on
+              inputs generated from successive seeds
+  report      Break down the input behind the answers, for days with a report
+
+Options:
+  -p, --part <part>     Which part(s) to run (default: both)
+  -i, --input <path>    Read the puzzle input from <path>, or stdin for '-'
+  -e, --example         Use the worked example from the puzzle text
+  -f, --format <fmt>    Output answers as text, json or csv (default: text)
+  -a, --answers <path>  Answers file for verify (default: inputs/answers.txt,
+                        or inputs/examples/answers.txt with --example)
+  -n, --reps <n>        Repetitions for bench (default: 5), or how many seeds
+                        to crosscheck (default: 10)
+  -o, --output <path>   Also write the bench results to <path>
+  -j, --jobs <n>        Run all days on <n> threads, then show a summary
+  -P, --param <n>=<v>   Override one of the day's puzzle parameters
+  -c, --config <path>   Read parameter overrides from <path>, with lines of
+                        '<day> <name> <value>'; --param takes precedence
+  -s, --size <n>        How big an input to generate (default: 10, or 4 for
+                        crosscheck)
+      --seed <n>        Seed for generate, which always gives the same
+                        input for the same seed, or the first seed for
+                        crosscheck (default: 0)
+  -v, --verbose         Show intermediate results on stderr; -vv for more";
+
+fn parse_part(value: Option<&String>) -> Result<Part, String> {
+    match value.map(|v| v.as_str()) {
+        Some("1") => Ok(Part::One),
+        Some("2") => Ok(Part::Two),
+        Some("both") => Ok(Part::Both),
+        Some(other) => Err(format!("Invalid part '{}': expected 1, 2 or both", other)),
+        None => Err("--part requires a value".to_string()),
+    }
+}
+
+fn parse_day(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(day) if (1..=DAYS.len()).contains(&day) => Ok(day),
+        _ => Err(format!(
+            "Unknown day '{}': expected a number from 1 to {}",
+            value,
+            DAYS.len()
+        )),
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<(Command, Options), String> {
+    let mut args = args.iter();
+    let command = match args.next() {
+        Some(arg) if arg == "all" => Command::All,
+        Some(arg) if arg == "verify" => Command::Verify,
+        Some(arg) if arg == "bench" => match args.next() {
+            Some(arg) if arg == "all" => Command::Bench(None),
+            Some(arg) => Command::Bench(Some(parse_day(arg)?)),
+            None => return Err("bench requires a day or 'all'".to_string()),
+        },
+        Some(arg) if arg == "generate" => match args.next() {
+            Some(arg) => Command::Generate(parse_day(arg)?),
+            None => return Err("generate requires a day".to_string()),
+        },
+        Some(arg) if arg == "crosscheck" => match args.next() {
+            Some(arg) if arg == "all" => Command::Crosscheck(None),
+            Some(arg) => Command::Crosscheck(Some(parse_day(arg)?)),
+            None => return Err("crosscheck requires a day or 'all'".to_string()),
+        },
+        Some(arg) if arg == "report" => match args.next() {
+            Some(arg) => Command::Report(parse_day(arg)?),
+            None => return Err("report requires a day".to_string()),
+        },
+        Some(arg) => Command::Day(parse_day(arg)?),
+        None => return Err("No day given".to_string()),
+    };
+
+    let mut options = Options {
+        part: Part::Both,
+        source: None,
+        answers: None,
+        reps: None,
+        output: None,
+        format: Format::Text,
+        jobs: None,
+        verbosity: 0,
+        settings: vec![],
+        config: None,
+        size: None,
+        seed: None,
+    };
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--part" | "-p" => options.part = parse_part(args.next())?,
+            "--input" | "-i" => match args.next() {
+                Some(path) => options.source = Some(InputSource::from_arg(path)),
+                None => return Err("--input requires a path".to_string()),
+            },
+            "--example" | "-e" => options.source = Some(InputSource::Example),
+            "--answers" | "-a" => match args.next() {
+                Some(path) => options.answers = Some(PathBuf::from(path)),
+                None => return Err("--answers requires a path".to_string()),
+            },
+            "--reps" | "-n" => match args.next().map(|n| n.parse()) {
+                Some(Ok(reps)) if reps > 0 => options.reps = Some(reps),
+                _ => return Err("--reps requires a positive number".to_string()),
+            },
+            "--format" | "-f" => match args.next().and_then(|f| Format::from_arg(f)) {
+                Some(format) => options.format = format,
+                None => return Err("--format requires text, json or csv".to_string()),
+            },
+            "--verbose" | "-v" => options.verbosity += 1,
+            "-vv" => options.verbosity += 2,
+            "--jobs" | "-j" => match args.next().map(|n| n.parse()) {
+                Some(Ok(jobs)) if jobs > 0 => options.jobs = Some(jobs),
+                _ => return Err("--jobs requires a positive number".to_string()),
+            },
+            "--param" | "-P" => {
+                let day = match command {
+                    Command::Day(day) | Command::Bench(Some(day)) | Command::Report(day) => day,
+                    _ => return Err("--param can only be used with a single day".to_string()),
+                };
+                match args.next().and_then(|arg| params::parse_setting(day, arg)) {
+                    Some(setting) => options.settings.push(setting),
+                    None => return Err("--param requires <name>=<value>".to_string()),
+                }
+            }
+            "--config" | "-c" => match args.next() {
+                Some(path) => options.config = Some(PathBuf::from(path)),
+                None => return Err("--config requires a path".to_string()),
+            },
+            "--size" | "-s" => match args.next().map(|n| n.parse()) {
+                Some(Ok(size)) => options.size = Some(size),
+                _ => return Err("--size requires a number".to_string()),
+            },
+            "--seed" => match args.next().map(|n| n.parse()) {
+                Some(Ok(seed)) => options.seed = Some(seed),
+                _ => return Err("--seed requires a number".to_string()),
+            },
+            "--output" | "-o" => match args.next() {
+                Some(path) => options.output = Some(PathBuf::from(path)),
+                None => return Err("--output requires a path".to_string()),
+            },
+            _ => return Err(format!("Unexpected argument '{}'", flag)),
+        }
+    }
+    if !matches!(
+        command,
+        Command::Day(_) | Command::Bench(Some(_)) | Command::Report(_)
+    ) && !matches!(options.source, None | Some(InputSource::Example))
+    {
+        return Err("--input can only be used with a single day".to_string());
+    }
+    if !matches!(command, Command::Verify) && options.answers.is_some() {
+        return Err("--answers can only be used with verify".to_string());
+    }
+    if matches!(command, Command::Bench(_)) {
+        if options.source == Some(InputSource::Stdin) {
+            return Err("bench reads its input repeatedly, so can't use stdin".to_string());
+        }
+    } else if options.output.is_some() {
+        return Err("--output can only be used with bench".to_string());
+    } else if options.reps.is_some() && !matches!(command, Command::Crosscheck(_)) {
+        return Err("--reps can only be used with bench or crosscheck".to_string());
+    }
+    if !matches!(command, Command::Day(_) | Command::All) && options.format != Format::Text {
+        return Err("--format can only be used when running days".to_string());
+    }
+    if !matches!(command, Command::All) && options.jobs.is_some() {
+        return Err("--jobs can only be used with all".to_string());
+    }
+    if !matches!(command, Command::Generate(_) | Command::Crosscheck(_))
+        && (options.size.is_some() || options.seed.is_some())
+    {
+        return Err("--size and --seed can only be used with generate or crosscheck".to_string());
+    }
+    if matches!(command, Command::Crosscheck(_)) && options.source.is_some() {
+        return Err("crosscheck generates its own inputs".to_string());
+    }
+    Ok((command, options))
+}
+
+// The input for a day, unless another was given.
+fn day_source(day: usize, options: &Options) -> InputSource {
+    match &options.source {
+        Some(source) => source.clone(),
+        None => InputSource::default_for_day(day),
+    }
+}
+
+fn run_day(day: usize, options: &Options) -> error::Result<Vec<Record>> {
+    let settings = params::for_day(&options.settings, day);
+    let solved = DAYS[day - 1](&day_source(day, options), options.part, &settings)
+        .map_err(|e| e.in_day(day))?;
+    Ok(solved
+        .answers
+        .into_iter()
+        .map(|answer| Record {
+            day,
+            part: answer.part,
+            answer: answer.value,
+            elapsed: answer.elapsed,
+        })
+        .collect())
+}
+
+fn run_bench(day: Option<usize>, options: &Options) -> Result<(), String> {
+    let days = match day {
+        Some(day) => day..=day,
+        None => 1..=DAYS.len(),
+    };
+    let mut results = vec![];
+    for day in days {
+        let settings = params::for_day(&options.settings, day);
+        let reps = options.reps.unwrap_or(BENCH_REPS);
+        let timings = bench::bench_day(
+            day,
+            &day_source(day, options),
+            options.part,
+            &settings,
+            reps,
+        )
+        .map_err(|e| e.to_string())?;
+        results.push((day, timings));
+    }
+    let table = bench::table(&results, options.reps.unwrap_or(BENCH_REPS));
+    print!("{}", table);
+    if let Some(path) = &options.output {
+        fs::write(path, table).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+// Returns whether every day succeeded.
+fn run_parallel(jobs: usize, options: &Options) -> bool {
+    let days: Vec<usize> = (1..=DAYS.len()).collect();
+    let start = Instant::now();
+    let results = parallel::run_days(&days, jobs, |day| run_day(day, options));
+    let wall_time = start.elapsed();
+
+    if options.format == Format::Text {
+        print!("{}", parallel::summary(&results, wall_time, jobs));
+    } else {
+        let mut records = vec![];
+        for result in &results {
+            match &result.outcome {
+                Ok(day_records) => records.extend(day_records.iter().cloned()),
+                Err(reason) => eprintln!("Error: day {:02}: {}", result.day, reason),
+            }
+        }
+        print!("{}", output::render(options.format, &records));
+    }
+    results.iter().all(|r| r.outcome.is_ok())
+}
+
+// Returns whether every recorded answer passed.
+fn verify(options: &Options) -> Result<bool, String> {
+    let example = options.source == Some(InputSource::Example);
+    let path = match &options.answers {
+        Some(path) => path.clone(),
+        None => verify::default_answers_path(example),
+    };
+    let expected = fs::read_to_string(&path)
+        .map_err(error::Error::from)
+        .and_then(|text| verify::read_answers(&text, DAYS.len()))
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let summary = verify::verify_answers(
+        &expected,
+        options.part,
+        options.source.as_ref(),
+        &options.settings,
+        |report| println!("{}", report),
+    );
+    println!("{}", summary);
+    Ok(summary.all_passed())
+}
+
+// Returns whether every answer agreed with the reference.
+fn crosscheck(only: Option<usize>, options: &Options) -> Result<bool, String> {
+    let days = match only {
+        Some(day) => day..=day,
+        None => 1..=DAYS.len(),
+    };
+    let size = options.size.unwrap_or(CROSSCHECK_SIZE);
+    let first_seed = options.seed.unwrap_or(0);
+    let seeds = first_seed..first_seed + options.reps.unwrap_or(CROSSCHECK_SEEDS) as u64;
+
+    let summary =
+        crosscheck::crosscheck_days(days, seeds, size, options.part, |day| print!("{}", day));
+    if let Some(day) = only.filter(|_| summary.is_empty()) {
+        return Err(format!("day {} has no reference solver", day));
+    }
+    println!("{}", summary);
+    if summary.disagreed > 0 {
+        println!(
+            "Reproduce an input with: aoc2022 generate <day> --size {} --seed <seed>",
+            size
+        );
+    }
+    Ok(summary.all_agreed())
+}
 
 fn main() {
-    day25::step1();
-    day25::step2();
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let (command, mut options) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(msg) => {
+            eprintln!("Error: {}\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+    log::set_level(options.verbosity);
+    if let Some(path) = &options.config {
+        let settings = fs::read_to_string(path)
+            .map_err(error::Error::from)
+            .and_then(|text| params::read_config(&text, DAYS.len()));
+        match settings {
+            Ok(settings) => {
+                options.settings.splice(0..0, settings);
+            }
+            Err(e) => {
+                eprintln!("Error: {}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+    }
+
+    let days = match command {
+        Command::Day(day) => day..=day,
+        Command::All => match options.jobs {
+            Some(jobs) => {
+                if !run_parallel(jobs, &options) {
+                    process::exit(1);
+                }
+                return;
+            }
+            None => 1..=DAYS.len(),
+        },
+        Command::Verify => match verify(&options) {
+            Ok(true) => return,
+            Ok(false) => process::exit(1),
+            Err(msg) => {
+                eprintln!("Error: {}", msg);
+                process::exit(1);
+            }
+        },
+        Command::Generate(day) => {
+            let size = options.size.unwrap_or(GENERATE_SIZE);
+            let generated = GENERATORS[day - 1](options.seed.unwrap_or(0), size);
+            print!("{}", generated.input);
+            if !generated.params.is_empty() {
+                let flags: Vec<String> = generated
+                    .params
+                    .iter()
+                    .map(|(name, value)| format!("--param {}={}", name, value))
+                    .collect();
+                eprintln!("Solve with: {}", flags.join(" "));
+            }
+            return;
+        }
+        Command::Crosscheck(day) => match crosscheck(day, &options) {
+            Ok(true) => return,
+            Ok(false) => process::exit(1),
+            Err(msg) => {
+                eprintln!("Error: {}", msg);
+                process::exit(1);
+            }
+        },
+        Command::Report(day) => {
+            let settings = params::for_day(&options.settings, day);
+            match REPORTS[day - 1](&day_source(day, &options), &settings) {
+                Ok(Some(report)) => print!("{}", report),
+                Ok(None) => {
+                    eprintln!("Error: day {} has no report", day);
+                    process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Error: {}", e.in_day(day));
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::Bench(day) => match run_bench(day, &options) {
+            Ok(()) => return,
+            Err(msg) => {
+                eprintln!("Error: {}", msg);
+                process::exit(1);
+            }
+        },
+    };
+    let mut records = vec![];
+    for day in days {
+        match run_day(day, &options) {
+            // Text is shown as each day finishes; other formats are a
+            // single document once all days have run.
+            Ok(day_records) if options.format == Format::Text => {
+                print!("{}", output::render(Format::Text, &day_records));
+            }
+            Ok(day_records) => records.extend(day_records),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+    if options.format != Format::Text {
+        print!("{}", output::render(options.format, &records));
+    }
 }
@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+/// How results of a run are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// One answer, in the schema shared by all formats.
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub day: usize,
+    pub part: u8,
+    pub answer: String,
+    pub elapsed: Duration,
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn text(records: &[Record]) -> String {
+    let mut out = String::new();
+    for r in records {
+        if r.answer.contains('\n') {
+            // Multi-line answers (e.g. rendered text) start on their own line
+            out += &format!("Day {:02} part {}:\n{}\n", r.day, r.part, r.answer);
+        } else {
+            out += &format!("Day {:02} part {}: {}\n", r.day, r.part, r.answer);
+        }
+    }
+    out
+}
+
+// A single array of objects, with the elapsed time in microseconds
+fn json(records: &[Record]) -> String {
+    let objects: Vec<String> = records
+        .iter()
+        .map(|r| {
+            format!(
+                "  {{\"day\": {}, \"part\": {}, \"answer\": {}, \"elapsed_us\": {}}}",
+                r.day,
+                r.part,
+                json_string(&r.answer),
+                r.elapsed.as_micros()
+            )
+        })
+        .collect();
+    if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+fn csv(records: &[Record]) -> String {
+    let mut out = String::from("day,part,answer,elapsed_us\n");
+    for r in records {
+        out += &format!(
+            "{},{},{},{}\n",
+            r.day,
+            r.part,
+            csv_field(&r.answer),
+            r.elapsed.as_micros()
+        );
+    }
+    out
+}
+
+pub fn render(format: Format, records: &[Record]) -> String {
+    match format {
+        Format::Text => text(records),
+        Format::Json => json(records),
+        Format::Csv => csv(records),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record {
+                day: 5,
+                part: 1,
+                answer: "CMZ".to_string(),
+                elapsed: Duration::from_micros(12),
+            },
+            Record {
+                day: 10,
+                part: 2,
+                answer: "# \"a\",\n #".to_string(),
+                elapsed: Duration::from_millis(3),
+            },
+        ]
+    }
+
+    #[test]
+    fn json_output() {
+        assert_eq!(
+            render(Format::Json, &records()),
+            "[\n  {\"day\": 5, \"part\": 1, \"answer\": \"CMZ\", \"elapsed_us\": 12},\n  \
+             {\"day\": 10, \"part\": 2, \"answer\": \"# \\\"a\\\",\\n #\", \"elapsed_us\": 3000}\n]\n"
+        );
+        assert_eq!(render(Format::Json, &[]), "[]\n");
+    }
+
+    #[test]
+    fn csv_output() {
+        assert_eq!(
+            render(Format::Csv, &records()),
+            "day,part,answer,elapsed_us\n5,1,CMZ,12\n10,2,\"# \"\"a\"\",\n #\",3000\n"
+        );
+    }
+
+    #[test]
+    fn text_output() {
+        assert_eq!(
+            render(Format::Text, &records()),
+            "Day 05 part 1: CMZ\nDay 10 part 2:\n# \"a\",\n #\n"
+        );
+    }
+}
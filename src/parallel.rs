@@ -0,0 +1,208 @@
+use std::{
+    any::Any,
+    fmt::Write,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{bench::format_duration, error, output::Record};
+
+/// How one day's run ended, and how long it took including reading input.
+#[derive(Debug)]
+pub struct DayResult {
+    pub day: usize,
+    /// The answers, or why the day failed.
+    pub outcome: Result<Vec<Record>, String>,
+    pub elapsed: Duration,
+}
+
+/// Describe the payload of a caught panic.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        format!("panicked: {}", msg)
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        format!("panicked: {}", msg)
+    } else {
+        "panicked".to_string()
+    }
+}
+
+/// Run `run`, turning a panic into an error message like a returned error.
+pub fn catch_panic<T>(run: impl FnOnce() -> error::Result<T>) -> Result<T, String> {
+    match panic::catch_unwind(AssertUnwindSafe(run)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(payload) => Err(panic_message(payload.as_ref())),
+    }
+}
+
+/// Run `run` without the default panic messages on stderr, for callers
+/// which report caught panics themselves, then put back the previous hook.
+///
+/// The panic hook is process-wide, so this silences panics on every
+/// thread meanwhile, and concurrent callers race to restore it.
+pub fn quietly<T>(run: impl FnOnce() -> T) -> T {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = run();
+    panic::set_hook(previous);
+    result
+}
+
+/// Run each day on up to `jobs` threads, with panics and errors recorded
+/// against the day rather than stopping the others, and reported in the
+/// results instead of on stderr. Results are in the order of `days`.
+pub fn run_days<F>(days: &[usize], jobs: usize, run: F) -> Vec<DayResult>
+where
+    F: Fn(usize) -> error::Result<Vec<Record>> + Sync,
+{
+    // Days are handed out one at a time, so a slow day doesn't hold up
+    // the others queued behind it on the same thread
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![]);
+    quietly(|| {
+        thread::scope(|scope| {
+            for _ in 0..jobs.clamp(1, days.len().max(1)) {
+                scope.spawn(|| {
+                    while let Some(&day) = days.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let start = Instant::now();
+                        let outcome = catch_panic(|| run(day));
+                        let elapsed = start.elapsed();
+                        results.lock().unwrap().push(DayResult {
+                            day,
+                            outcome,
+                            elapsed,
+                        });
+                    }
+                });
+            }
+        })
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|r| days.iter().position(|&d| d == r.day));
+    results
+}
+
+// Multi-line answers don't fit in the table, so are shown after it.
+fn answer_cell(records: &[Record], part: u8) -> String {
+    match records.iter().find(|r| r.part == part) {
+        Some(r) if r.answer.contains('\n') => "(see below)".to_string(),
+        Some(r) => r.answer.clone(),
+        None => "-".to_string(),
+    }
+}
+
+/// Table of each day's status, time and answers, followed by any
+/// multi-line answers and failure reasons.
+pub fn summary(results: &[DayResult], wall_time: Duration, jobs: usize) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{:<4} {:<6} {:>12}  {:<20} Part 2",
+        "Day", "Status", "Time", "Part 1"
+    )
+    .unwrap();
+    let mut details = String::new();
+    for result in results {
+        let (status, part1, part2) = match &result.outcome {
+            Ok(records) => {
+                for r in records.iter().filter(|r| r.answer.contains('\n')) {
+                    writeln!(details, "Day {:02} part {}:\n{}", r.day, r.part, r.answer).unwrap();
+                }
+                ("ok", answer_cell(records, 1), answer_cell(records, 2))
+            }
+            Err(reason) => {
+                writeln!(details, "Day {:02} failed: {}", result.day, reason).unwrap();
+                ("FAIL", "-".to_string(), "-".to_string())
+            }
+        };
+        let row = format!(
+            "{:<4} {:<6} {:>12}  {:<20} {:<20}",
+            format!("{:02}", result.day),
+            status,
+            format_duration(result.elapsed),
+            part1,
+            part2
+        );
+        writeln!(out, "{}", row.trim_end()).unwrap();
+    }
+    let failed = results.iter().filter(|r| r.outcome.is_err()).count();
+    writeln!(
+        out,
+        "{} days on {} threads in {}: {} ok, {} failed",
+        results.len(),
+        jobs,
+        format_duration(wall_time),
+        results.len() - failed,
+        failed
+    )
+    .unwrap();
+    if !details.is_empty() {
+        out.push('\n');
+        out.push_str(&details);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn record(day: usize, part: u8, answer: &str) -> Record {
+        Record {
+            day,
+            part,
+            answer: answer.to_string(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn failures_are_captured_per_day() {
+        panic::set_hook(Box::new(|_| {}));
+        let results = run_days(&[1, 2, 3, 4], 3, |day| match day {
+            2 => panic!("day two"),
+            3 => Err(Error::new("bad input")),
+            _ => Ok(vec![record(day, 1, "42")]),
+        });
+        let _ = panic::take_hook();
+        let days: Vec<usize> = results.iter().map(|r| r.day).collect();
+        assert_eq!(days, vec![1, 2, 3, 4]);
+        assert!(results[0].outcome.is_ok());
+        assert_eq!(
+            results[1].outcome.as_ref().unwrap_err(),
+            "panicked: day two"
+        );
+        assert_eq!(results[2].outcome.as_ref().unwrap_err(), "bad input");
+        assert!(results[3].outcome.is_ok());
+    }
+
+    #[test]
+    fn summary_table() {
+        let results = vec![
+            DayResult {
+                day: 9,
+                outcome: Ok(vec![record(9, 1, "13"), record(9, 2, "#.\n.#")]),
+                elapsed: Duration::from_millis(2),
+            },
+            DayResult {
+                day: 22,
+                outcome: Err("panicked: oops".to_string()),
+                elapsed: Duration::from_millis(1),
+            },
+        ];
+        let table = summary(&results, Duration::from_millis(3), 2);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[1].starts_with("09   ok"));
+        assert!(lines[1].contains("13") && lines[1].contains("(see below)"));
+        assert!(lines[2].starts_with("22   FAIL"));
+        assert_eq!(lines[3], "2 days on 2 threads in 3.000 ms: 1 ok, 1 failed");
+        assert!(table.ends_with("Day 09 part 2:\n#.\n.#\nDay 22 failed: panicked: oops\n"));
+    }
+}
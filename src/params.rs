@@ -0,0 +1,170 @@
+use std::str::FromStr;
+
+use crate::{
+    error::{Error, Result},
+    input::numbered_lines,
+};
+
+/// A puzzle constant which isn't part of the input, such as the row
+/// day 15 checks, so can differ between the worked example and the real
+/// puzzle, or for variant inputs.
+#[derive(Debug, Clone, Copy)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: &'static str,
+    /// The value for the worked example, if different from the default.
+    pub example: Option<&'static str>,
+}
+
+/// The value of each of a day's parameters for one run.
+#[derive(Debug, Clone)]
+pub struct Params {
+    values: Vec<(&'static str, String)>,
+}
+
+impl Params {
+    /// The defaults, or the example's values for the worked example.
+    pub fn new(params: &[Param], example: bool) -> Self {
+        let values = params
+            .iter()
+            .map(|p| {
+                let value = match p.example {
+                    Some(value) if example => value,
+                    _ => p.default,
+                };
+                (p.name, value.to_string())
+            })
+            .collect();
+        Self { values }
+    }
+
+    /// Override one value; names the day doesn't declare are an error.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        if let Some((_, v)) = self.values.iter_mut().find(|(n, _)| *n == name) {
+            *v = value.to_string();
+            return Ok(());
+        }
+        if self.values.is_empty() {
+            return Err(Error::new(format!(
+                "unknown parameter '{}'; this day has none",
+                name
+            )));
+        }
+        let names: Vec<&str> = self.values.iter().map(|(n, _)| *n).collect();
+        Err(Error::new(format!(
+            "unknown parameter '{}'; expected {}",
+            name,
+            names.join(", ")
+        )))
+    }
+
+    /// The value of a parameter declared by the day.
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T> {
+        let (_, value) = self
+            .values
+            .iter()
+            .find(|(n, _)| *n == name)
+            .unwrap_or_else(|| panic!("parameter '{}' isn't declared", name));
+        value.parse().map_err(|_| {
+            Error::new(format!(
+                "invalid value '{}' for parameter '{}'",
+                value, name
+            ))
+        })
+    }
+}
+
+/// A parameter value for one day, from a config file or the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting {
+    pub day: usize,
+    pub name: String,
+    pub value: String,
+}
+
+/// Config files have one `<day> <name> <value>` entry per line, with
+/// `#` comment lines.
+pub fn read_config(input: &str, day_count: usize) -> Result<Vec<Setting>> {
+    let mut settings = vec![];
+    for line in numbered_lines(input)? {
+        if line.text.is_empty() || line.text.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.text.split_whitespace();
+        let day_field = line.field(fields.next(), "day")?;
+        let day: usize = line.parse(day_field)?;
+        if !(1..=day_count).contains(&day) {
+            return Err(line.field_error(day_field, format!("day must be 1 to {}", day_count)));
+        }
+        let name = line.field(fields.next(), "parameter name")?;
+        let value = line.field(fields.next(), "value")?;
+        if let Some(extra) = fields.next() {
+            return Err(line.field_error(extra, "unexpected text after the value"));
+        }
+        settings.push(Setting {
+            day,
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+    }
+    Ok(settings)
+}
+
+/// The overrides which apply to one day.
+pub fn for_day(settings: &[Setting], day: usize) -> Vec<Setting> {
+    settings.iter().filter(|s| s.day == day).cloned().collect()
+}
+
+/// A `<name>=<value>` command line override.
+pub fn parse_setting(day: usize, arg: &str) -> Option<Setting> {
+    let (name, value) = arg.split_once('=')?;
+    if name.is_empty() || value.is_empty() {
+        return None;
+    }
+    Some(Setting {
+        day,
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: &[Param] = &[
+        Param {
+            name: "row",
+            default: "2000000",
+            example: Some("10"),
+        },
+        Param {
+            name: "start",
+            default: "AA",
+            example: None,
+        },
+    ];
+
+    #[test]
+    fn defaults_and_overrides() {
+        let mut params = Params::new(PARAMS, false);
+        assert_eq!(params.get::<i32>("row").unwrap(), 2000000);
+        assert_eq!(Params::new(PARAMS, true).get::<i32>("row").unwrap(), 10);
+        assert_eq!(params.get::<String>("start").unwrap(), "AA");
+        params.set("row", "x").unwrap();
+        assert!(params.get::<i32>("row").is_err());
+        let e = params.set("rows", "5").unwrap_err();
+        assert_eq!(e.message, "unknown parameter 'rows'; expected row, start");
+    }
+
+    #[test]
+    fn config_file() {
+        let settings = read_config("# comment\n15 row 10\n\n16 start BB\n", 25).unwrap();
+        assert_eq!(settings.len(), 2);
+        assert_eq!(settings[1], parse_setting(16, "start=BB").unwrap());
+        let e = read_config("15 row\n", 25).unwrap_err();
+        assert_eq!(e.line, Some(1));
+        assert!(read_config("26 row 10\n", 25).is_err());
+        assert!(parse_setting(15, "row").is_none());
+    }
+}
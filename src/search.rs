@@ -0,0 +1,222 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// A graph explored one node at a time through its neighbours.
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    /// Nodes reachable in one step from `node`.
+    fn neighbours(&self, node: &Self::Node) -> Vec<Self::Node>;
+
+    /// The cost of the step between neighbours; ignored by `bfs()`.
+    fn cost(&self, _from: &Self::Node, _to: &Self::Node) -> usize {
+        1
+    }
+}
+
+/// The outcome of a search: the distance to each node reached, and how
+/// it was reached.
+#[derive(Debug)]
+pub struct Search<N> {
+    distance: HashMap<N, usize>,
+    previous: HashMap<N, N>,
+    target: Option<N>,
+}
+
+impl<N: Clone + Eq + Hash> Search<N> {
+    fn new() -> Self {
+        Self {
+            distance: HashMap::new(),
+            previous: HashMap::new(),
+            target: None,
+        }
+    }
+
+    /// The first target found, which is a nearest one.
+    pub fn target(&self) -> Option<&N> {
+        self.target.as_ref()
+    }
+
+    /// Distance to the target found, if any.
+    pub fn target_distance(&self) -> Option<usize> {
+        self.distance(self.target.as_ref()?)
+    }
+
+    /// Shortest distance from any source, if the node was reached.
+    ///
+    /// When the search stops at a target, nodes further away than it may
+    /// be missing or have overestimates.
+    pub fn distance(&self, node: &N) -> Option<usize> {
+        self.distance.get(node).copied()
+    }
+
+    /// Every node reached, with its distance.
+    pub fn distances(&self) -> &HashMap<N, usize> {
+        &self.distance
+    }
+
+    /// Nodes from a source to `node`, inclusive.
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        self.distance.get(node)?;
+        let mut path = vec![node.clone()];
+        while let Some(previous) = self.previous.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Path from a source to the target found.
+    pub fn target_path(&self) -> Option<Vec<N>> {
+        self.path(self.target.as_ref()?)
+    }
+}
+
+/// Breadth first search, counting every step as 1, until a node
+/// matching `is_target` is reached or the graph is exhausted.
+pub fn bfs<G: Graph>(
+    graph: &G,
+    sources: impl IntoIterator<Item = G::Node>,
+    mut is_target: impl FnMut(&G::Node) -> bool,
+) -> Search<G::Node> {
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for source in sources {
+        if !search.distance.contains_key(&source) {
+            search.distance.insert(source.clone(), 0);
+            queue.push_back(source);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        if is_target(&node) {
+            search.target = Some(node);
+            break;
+        }
+        let next_distance = search.distance[&node] + 1;
+        for next in graph.neighbours(&node) {
+            if !search.distance.contains_key(&next) {
+                search.distance.insert(next.clone(), next_distance);
+                search.previous.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Dijkstra's algorithm, using the graph's step costs.
+pub fn dijkstra<G: Graph>(
+    graph: &G,
+    sources: impl IntoIterator<Item = G::Node>,
+    is_target: impl FnMut(&G::Node) -> bool,
+) -> Search<G::Node> {
+    astar(graph, sources, is_target, |_| 0)
+}
+
+/// A* search, guided by a `heuristic` which must never overestimate the
+/// remaining distance to a target.
+pub fn astar<G: Graph>(
+    graph: &G,
+    sources: impl IntoIterator<Item = G::Node>,
+    mut is_target: impl FnMut(&G::Node) -> bool,
+    heuristic: impl Fn(&G::Node) -> usize,
+) -> Search<G::Node> {
+    let mut search = Search::new();
+    // Nodes are queued by index, so they needn't be ordered themselves
+    let mut queued: Vec<G::Node> = vec![];
+    let mut heap = BinaryHeap::new();
+    for source in sources {
+        if !search.distance.contains_key(&source) {
+            search.distance.insert(source.clone(), 0);
+            heap.push(Reverse((heuristic(&source), 0, queued.len())));
+            queued.push(source);
+        }
+    }
+    while let Some(Reverse((_, cost, idx))) = heap.pop() {
+        let node = queued[idx].clone();
+        // Skip stale entries, where a cheaper way was found after queueing
+        if cost > search.distance[&node] {
+            continue;
+        }
+        if is_target(&node) {
+            search.target = Some(node);
+            break;
+        }
+        for next in graph.neighbours(&node) {
+            let next_cost = cost + graph.cost(&node, &next);
+            if search.distance.get(&next).is_none_or(|&d| next_cost < d) {
+                search.distance.insert(next.clone(), next_cost);
+                search.previous.insert(next.clone(), node.clone());
+                heap.push(Reverse((
+                    next_cost + heuristic(&next),
+                    next_cost,
+                    queued.len(),
+                )));
+                queued.push(next);
+            }
+        }
+    }
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 3 is two steps but costs 10; 0 -> 2 -> 4 -> 3 costs 3.
+    struct Weighted;
+
+    impl Graph for Weighted {
+        type Node = u32;
+
+        fn neighbours(&self, node: &u32) -> Vec<u32> {
+            match node {
+                0 => vec![1, 2],
+                1 => vec![3],
+                2 => vec![4],
+                4 => vec![3],
+                _ => vec![],
+            }
+        }
+
+        fn cost(&self, from: &u32, to: &u32) -> usize {
+            if (*from, *to) == (1, 3) {
+                9
+            } else {
+                1
+            }
+        }
+    }
+
+    #[test]
+    fn bfs_counts_steps() {
+        let search = bfs(&Weighted, [0], |&n| n == 3);
+        assert_eq!(search.target_distance(), Some(2));
+        assert_eq!(search.target_path(), Some(vec![0, 1, 3]));
+    }
+
+    #[test]
+    fn dijkstra_uses_costs() {
+        let search = dijkstra(&Weighted, [0], |&n| n == 3);
+        assert_eq!(search.target_distance(), Some(3));
+        assert_eq!(search.target_path(), Some(vec![0, 2, 4, 3]));
+        let all = dijkstra(&Weighted, [0], |_| false);
+        assert_eq!(all.distances().len(), 5);
+        assert_eq!(all.distance(&1), Some(1));
+    }
+
+    #[test]
+    fn astar_and_multiple_sources() {
+        let search = astar(&Weighted, [0], |&n| n == 3, |&n| (n != 3) as usize);
+        assert_eq!(search.target_distance(), Some(3));
+        let search = bfs(&Weighted, [4, 0], |&n| n == 3 || n == 1);
+        assert_eq!(search.target(), Some(&3));
+        assert_eq!(search.target_path(), Some(vec![4, 3]));
+        let search = bfs(&Weighted, [3], |&n| n == 0);
+        assert_eq!(search.target(), None);
+        assert_eq!(search.path(&0), None);
+    }
+}
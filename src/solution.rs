@@ -0,0 +1,164 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::Result,
+    generate::{Generated, Rng},
+    input::InputSource,
+    params::{Param, Params, Setting},
+};
+
+/// Common interface implemented by each day's puzzle.
+///
+/// The input is parsed once, and both parts then compute their answer
+/// from the parsed model rather than printing it.
+pub trait Solution {
+    /// Parsed puzzle input shared by both parts.
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    /// The worked example from the puzzle text.
+    const EXAMPLE: &'static str;
+
+    /// Constants the puzzle gives outside the input, which the parsed
+    /// input carries to the parts.
+    const PARAMS: &'static [Param] = &[];
+
+    /// Slow but plainly correct versions of the parts, for days whose fast
+    /// ones rely on a heuristic or shortcut. `crosscheck` compares the two
+    /// on small generated inputs.
+    const REFERENCE1: Option<Reference<Self::Input, Self::Answer1>> = None;
+    const REFERENCE2: Option<Reference<Self::Input, Self::Answer2>> = None;
+
+    /// A breakdown of the parsed input behind the answers, for the
+    /// `report` command, on days which have one.
+    const REPORT: Option<fn(&Self::Input) -> String> = None;
+
+    /// Reject malformed input with an error locating the problem, rather
+    /// than panicking part way through solving.
+    fn parse(input: &str, params: &Params) -> Result<Self::Input>;
+
+    /// Fail for inputs which parse but have no answer, such as a map
+    /// without a route, instead of panicking.
+    fn part1(input: &Self::Input) -> Result<Self::Answer1>;
+    fn part2(input: &Self::Input) -> Result<Self::Answer2>;
+
+    /// A random but valid input for stress testing, growing with `size`;
+    /// each day says what it counts.
+    fn generate(rng: &mut Rng, size: usize) -> Generated;
+}
+
+/// A part's solution as a plain function of the parsed input.
+pub type Reference<I, A> = fn(&I) -> A;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+    Both,
+}
+
+impl Part {
+    pub fn includes(&self, part: u8) -> bool {
+        match self {
+            Part::One => part == 1,
+            Part::Two => part == 2,
+            Part::Both => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Answer {
+    pub part: u8,
+    pub value: String,
+    /// Time taken to compute the answer from the parsed input.
+    pub elapsed: Duration,
+}
+
+/// The answers for one run of a day, and how long parsing took.
+#[derive(Debug, Clone)]
+pub struct Solved {
+    pub parse_time: Duration,
+    pub answers: Vec<Answer>,
+}
+
+/// A day's entry point, solving the input for some of the parts. The
+/// settings override the day's parameter defaults, in order.
+pub type Solver = fn(&InputSource, Part, &[Setting]) -> Result<Solved>;
+
+// The day's parameters, with the settings applied to its defaults.
+fn params<S: Solution>(source: &InputSource, settings: &[Setting]) -> Result<Params> {
+    let mut params = Params::new(S::PARAMS, *source == InputSource::Example);
+    for setting in settings {
+        params.set(&setting.name, &setting.value)?;
+    }
+    Ok(params)
+}
+
+pub fn solve<S: Solution>(
+    source: &InputSource,
+    part: Part,
+    settings: &[Setting],
+) -> Result<Solved> {
+    let params = params::<S>(source, settings)?;
+    // Reading the input isn't included in the parse time
+    let text = source.read(S::EXAMPLE)?;
+    let start = Instant::now();
+    let input = S::parse(&text, &params)?;
+    let parse_time = start.elapsed();
+
+    let mut answers = vec![];
+    if part.includes(1) {
+        let start = Instant::now();
+        let value = S::part1(&input)?.to_string();
+        answers.push(Answer {
+            part: 1,
+            value,
+            elapsed: start.elapsed(),
+        });
+    }
+    if part.includes(2) {
+        let start = Instant::now();
+        let value = S::part2(&input)?.to_string();
+        answers.push(Answer {
+            part: 2,
+            value,
+            elapsed: start.elapsed(),
+        });
+    }
+    Ok(Solved {
+        parse_time,
+        answers,
+    })
+}
+
+/// A day's report on its input, which is None for days without one.
+pub type Reporter = fn(&InputSource, &[Setting]) -> Result<Option<String>>;
+
+pub fn report<S: Solution>(source: &InputSource, settings: &[Setting]) -> Result<Option<String>> {
+    let report = match S::REPORT {
+        Some(report) => report,
+        None => return Ok(None),
+    };
+    let params = params::<S>(source, settings)?;
+    let input = S::parse(&source.read(S::EXAMPLE)?, &params)?;
+    Ok(Some(report(&input)))
+}
+
+/// Parse the worked example, for tests.
+#[cfg(test)]
+pub fn example_input<S: Solution>() -> S::Input {
+    S::parse(S::EXAMPLE, &Params::new(S::PARAMS, true)).unwrap()
+}
+
+/// Parse the committed puzzle input for a day, for tests checking the
+/// known answers.
+#[cfg(test)]
+pub fn committed_input<S: Solution>(day: usize) -> S::Input {
+    let text = InputSource::default_for_day(day).read("").unwrap();
+    S::parse(&text, &Params::new(S::PARAMS, false)).unwrap()
+}
@@ -0,0 +1,281 @@
+use std::{fmt, path::PathBuf};
+
+use crate::{
+    error::{Error, Result},
+    input::{numbered_lines, InputSource},
+    parallel::{catch_panic, quietly},
+    params::{self, Setting},
+    solution::Part,
+    DAYS,
+};
+
+/// A recorded answer for one part of a day.
+///
+/// Answers files have one `<day> <part> <answer>` entry per line, with
+/// `#` comment lines. Multi-line answers are written on a single line
+/// with `\n` between the lines, and `\\` for a literal backslash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub day: usize,
+    pub part: u8,
+    pub value: String,
+}
+
+/// The answers file for the committed inputs, or for the worked examples.
+pub fn default_answers_path(example: bool) -> PathBuf {
+    if example {
+        PathBuf::from("inputs/examples/answers.txt")
+    } else {
+        PathBuf::from("inputs/answers.txt")
+    }
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                'n' => result.push('\n'),
+                '\\' => result.push('\\'),
+                _ => return None,
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Some(result)
+}
+
+pub fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+pub fn read_answers(input: &str, day_count: usize) -> Result<Vec<Expected>> {
+    let mut answers: Vec<Expected> = vec![];
+    for line in numbered_lines(input)? {
+        if line.text.is_empty() || line.text.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.text.splitn(3, ' ');
+        let day_field = line.field(fields.next(), "day")?;
+        let day: usize = line.parse(day_field)?;
+        if !(1..=day_count).contains(&day) {
+            return Err(line.field_error(day_field, format!("day must be 1 to {}", day_count)));
+        }
+        let part_field = line.field(fields.next(), "part")?;
+        let part: u8 = line.parse(part_field)?;
+        if part != 1 && part != 2 {
+            return Err(line.field_error(part_field, "part must be 1 or 2"));
+        }
+        let value_field = line.field(fields.next(), "answer")?;
+        let value = unescape(value_field)
+            .ok_or_else(|| line.field_error(value_field, "invalid escape; use \\n or \\\\"))?;
+        if answers.iter().any(|a| a.day == day && a.part == part) {
+            return Err(line.error(format!("duplicate answer for day {} part {}", day, part)));
+        }
+        answers.push(Expected { day, part, value });
+    }
+    if answers.is_empty() {
+        return Err(Error::new("no answers recorded"));
+    }
+    Ok(answers)
+}
+
+// Rendered answers may have trailing spaces, which aren't significant
+fn normalise(value: &str) -> String {
+    value
+        .lines()
+        .map(|l| l.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    Pass,
+    Mismatch {
+        actual: String,
+    },
+    /// The solver returned an error or panicked.
+    Fail {
+        reason: String,
+    },
+}
+
+impl Outcome {
+    pub fn check(expected: &Expected, actual: &str) -> Self {
+        if normalise(&expected.value) == normalise(actual) {
+            Outcome::Pass
+        } else {
+            Outcome::Mismatch {
+                actual: actual.to_string(),
+            }
+        }
+    }
+}
+
+/// An outcome together with what was expected, for reporting.
+pub struct Report<'a> {
+    pub expected: &'a Expected,
+    pub outcome: Outcome,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Day {:02} part {}: ",
+            self.expected.day, self.expected.part
+        )?;
+        match &self.outcome {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Mismatch { actual } => write!(
+                f,
+                "MISMATCH: expected {}, got {}",
+                escape(&self.expected.value),
+                escape(actual)
+            ),
+            Outcome::Fail { reason } => write!(f, "FAIL: {}", reason),
+        }
+    }
+}
+
+/// Solve the part of the day an answer was recorded for, on its own so
+/// that one part can't hide the result of the other.
+pub fn verify_answer(expected: &Expected, source: &InputSource, settings: &[Setting]) -> Outcome {
+    let part = if expected.part == 1 {
+        Part::One
+    } else {
+        Part::Two
+    };
+    match catch_panic(|| DAYS[expected.day - 1](source, part, settings)) {
+        Ok(solved) => match solved.answers.first() {
+            Some(answer) => Outcome::check(expected, &answer.value),
+            None => Outcome::Fail {
+                reason: "no answer".to_string(),
+            },
+        },
+        Err(reason) => Outcome::Fail { reason },
+    }
+}
+
+/// Counts of each outcome over a set of answers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub passed: usize,
+    pub mismatched: usize,
+    pub failed: usize,
+}
+
+impl Summary {
+    pub fn all_passed(&self) -> bool {
+        self.mismatched == 0 && self.failed == 0
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} passed, {} mismatched, {} failed",
+            self.passed, self.mismatched, self.failed
+        )
+    }
+}
+
+/// Verify each of the answers for the given parts, passing the report for
+/// each to `on_report` as it's checked. Days use their committed input
+/// unless `source` is given, and the settings which apply to them.
+pub fn verify_answers(
+    answers: &[Expected],
+    part: Part,
+    source: Option<&InputSource>,
+    settings: &[Setting],
+    mut on_report: impl FnMut(&Report),
+) -> Summary {
+    let mut summary = Summary::default();
+    // Solver panics are reported as failures rather than on stderr
+    quietly(|| {
+        for expected in answers.iter().filter(|e| part.includes(e.part)) {
+            let day_source = match source {
+                Some(source) => source.clone(),
+                None => InputSource::default_for_day(expected.day),
+            };
+            let day_settings = params::for_day(settings, expected.day);
+            let outcome = verify_answer(expected, &day_source, &day_settings);
+            match outcome {
+                Outcome::Pass => summary.passed += 1,
+                Outcome::Mismatch { .. } => summary.mismatched += 1,
+                Outcome::Fail { .. } => summary.failed += 1,
+            }
+            on_report(&Report { expected, outcome });
+        }
+    });
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_answers_file() {
+        let answers = read_answers("# comment\n1 1 24000\n10 2 ##\\n #\n", 25).unwrap();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[1].day, 10);
+        assert_eq!(answers[1].value, "##\n #");
+    }
+
+    #[test]
+    fn reject_bad_entries() {
+        let e = read_answers("1 1 2\n26 1 5\n", 25).unwrap_err();
+        assert_eq!((e.line, e.column), (Some(2), Some(1)));
+        let e = read_answers("1 3 2\n", 25).unwrap_err();
+        assert_eq!(e.column, Some(3));
+        assert!(read_answers("1 1 2\n1 1 3\n", 25).is_err());
+    }
+
+    #[test]
+    fn verify_example_answers() {
+        let answers = read_answers("1 1 24000\n1 2 0\n2 1 15\n", 25).unwrap();
+        let mut reported = vec![];
+        let summary = verify_answers(
+            &answers,
+            Part::Both,
+            Some(&InputSource::Example),
+            &[],
+            |report| reported.push(report.to_string()),
+        );
+        assert_eq!(summary.to_string(), "2 passed, 1 mismatched, 0 failed");
+        assert_eq!(
+            reported[1],
+            "Day 01 part 2: MISMATCH: expected 0, got 45000"
+        );
+        let part1 = verify_answers(
+            &answers,
+            Part::One,
+            Some(&InputSource::Example),
+            &[],
+            |_| {},
+        );
+        assert!(part1.all_passed());
+    }
+
+    #[test]
+    fn trailing_spaces_ignored() {
+        let expected = Expected {
+            day: 10,
+            part: 2,
+            value: "# \n #".to_string(),
+        };
+        assert!(matches!(
+            Outcome::check(&expected, "#\n #  "),
+            Outcome::Pass
+        ));
+        assert!(matches!(
+            Outcome::check(&expected, "#\n# "),
+            Outcome::Mismatch { .. }
+        ));
+    }
+}